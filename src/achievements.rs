@@ -0,0 +1,166 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Duration, NaiveDate, Timelike};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AchievementConfig;
+use crate::stats::Stats;
+use crate::wr::WRs;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Achievement {
+    /// The identifier of the badge, e.g. for icons in the web UI
+    pub id: String,
+    /// The name of the badge
+    pub name: String,
+    /// Why the badge was earned
+    pub description: String,
+}
+
+struct Rule {
+    id: &'static str,
+    name: &'static str,
+    // Returns the description if the badge was earned
+    check: fn(&WRs, &Stats, &AchievementConfig) -> Option<String>,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        id: "early-bird",
+        name: "Early Bird",
+        check: early_bird,
+    },
+    Rule {
+        id: "night-owl",
+        name: "Night Owl",
+        check: night_owl,
+    },
+    Rule {
+        id: "perfect-month",
+        name: "Perfect Month",
+        check: perfect_month,
+    },
+    Rule {
+        id: "novelist",
+        name: "Novelist",
+        check: novelist,
+    },
+    Rule {
+        id: "ghosted",
+        name: "Ghosted",
+        check: ghosted,
+    },
+];
+
+fn early_bird(wrs: &WRs, _stats: &Stats, config: &AchievementConfig) -> Option<String> {
+    let count = wrs
+        .wrs
+        .iter()
+        .filter(|wr| {
+            let hour = wr.sent.env.date.hour();
+            (config.early_bird_start_hour..config.early_bird_hour).contains(&hour)
+        })
+        .count();
+    (count > 0).then(|| {
+        format!(
+            "Sent {} WRs between {}:00 and {}:00",
+            count, config.early_bird_start_hour, config.early_bird_hour
+        )
+    })
+}
+
+fn night_owl(wrs: &WRs, _stats: &Stats, config: &AchievementConfig) -> Option<String> {
+    let count = wrs
+        .wrs
+        .iter()
+        .filter(|wr| wr.sent.env.date.hour() >= config.night_owl_hour)
+        .count();
+    (count > 0).then(|| format!("Sent {} WRs after {}:00", count, config.night_owl_hour))
+}
+
+fn perfect_month(wrs: &WRs, stats: &Stats, _config: &AchievementConfig) -> Option<String> {
+    // The (ISO year, week) of every WR that was sent
+    let weeks: HashSet<(i32, u32)> = wrs
+        .wrs
+        .iter()
+        .map(|wr| {
+            let week = wr.sent.env.date.iso_week();
+            (week.year(), week.week())
+        })
+        .collect();
+
+    // A month is perfect if there is a WR in every week that starts in it
+    let mut months: HashMap<u32, bool> = HashMap::new();
+    let mut day = NaiveDate::from_ymd_opt(stats.year as i32, 1, 1)?;
+    while day.year() == stats.year as i32 {
+        if day.weekday().num_days_from_monday() == 0 {
+            let week = day.iso_week();
+            let sent = weeks.contains(&(week.year(), week.week()));
+            *months.entry(day.month()).or_insert(true) &= sent;
+        }
+        day += Duration::days(1);
+    }
+
+    let mut perfect: Vec<u32> = months
+        .into_iter()
+        .filter(|(_, perfect)| *perfect)
+        .map(|(month, _)| month)
+        .collect();
+    perfect.sort();
+    match perfect.len() {
+        0 => None,
+        1 => {
+            let month = NaiveDate::from_ymd_opt(stats.year as i32, perfect[0], 1)?;
+            Some(format!("Sent a WR every week in {}", month.format("%B")))
+        }
+        n => Some(format!("Sent a WR every week in {} months", n)),
+    }
+}
+
+fn novelist(_wrs: &WRs, stats: &Stats, config: &AchievementConfig) -> Option<String> {
+    let longest = stats.longest_wr.as_ref()?;
+    (longest.num_words > config.novelist_words).then(|| {
+        format!(
+            "Wrote {} words in the WR of {}",
+            longest.num_words, longest.date
+        )
+    })
+}
+
+fn ghosted(wrs: &WRs, _stats: &Stats, config: &AchievementConfig) -> Option<String> {
+    // Find the longest time span covered by consecutive WRs without a reply
+    let mut dates: Vec<_> = wrs
+        .wrs
+        .iter()
+        .map(|wr| (wr.sent.env.date, wr.reply.is_some()))
+        .collect();
+    dates.sort_by_key(|(date, _)| *date);
+
+    let mut streak_start = None;
+    let mut longest = 0;
+    for (date, replied) in dates {
+        if replied {
+            streak_start = None;
+            continue;
+        }
+        let start = *streak_start.get_or_insert(date);
+        // A single unreplied WR already covers the week it was sent in
+        let weeks = (date - start).num_days() as usize / 7 + 1;
+        longest = longest.max(weeks);
+    }
+    (longest >= config.ghosted_weeks).then(|| format!("No reply for {} weeks in a row", longest))
+}
+
+pub fn evaluate(wrs: &WRs, stats: &Stats, config: &AchievementConfig) -> Vec<Achievement> {
+    RULES
+        .iter()
+        .filter_map(|rule| {
+            (rule.check)(wrs, stats, config).map(|description| Achievement {
+                id: rule.id.to_string(),
+                name: rule.name.to_string(),
+                description,
+            })
+        })
+        .collect()
+}
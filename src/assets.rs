@@ -0,0 +1,50 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+
+use rust_embed::RustEmbed;
+
+// The frontend, compiled into the binary so it works from any directory
+#[derive(RustEmbed)]
+#[folder = "web/"]
+struct WebAssets;
+
+#[derive(Debug, Clone)]
+pub enum Assets {
+    // The assets that were embedded at compile time
+    Embedded,
+    // The assets of a directory, e.g. while developing the frontend
+    Directory(PathBuf),
+}
+
+impl Assets {
+    pub fn new(web_dir: Option<&str>) -> Self {
+        match web_dir {
+            Some(dir) => Assets::Directory(PathBuf::from(dir)),
+            None => Assets::Embedded,
+        }
+    }
+
+    // The contents and MIME type of an asset such as "js/main.js"
+    pub fn get(&self, path: &str) -> Option<(Cow<'static, [u8]>, String)> {
+        // Do not leave the asset directory
+        if path.split('/').any(|part| part == "..") {
+            return None;
+        }
+        match self {
+            Assets::Embedded => {
+                WebAssets::get(path).map(|file| (file.data, file.metadata.mimetype().to_string()))
+            }
+            Assets::Directory(dir) => {
+                let data = fs::read(dir.join(path)).ok()?;
+                let mimetype = mime_guess::from_path(path).first_or_octet_stream();
+                Some((Cow::Owned(data), mimetype.to_string()))
+            }
+        }
+    }
+
+    pub fn get_string(&self, path: &str) -> Option<String> {
+        self.get(path)
+            .map(|(data, _)| String::from_utf8_lossy(&data).into_owned())
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+
+use log::info;
+use resvg::{tiny_skia, usvg};
+
+use crate::error::{Result, WrError};
+use crate::stats::{self, Stats};
+
+const WIDTH: u32 = 1080;
+const HEIGHT: u32 = 1350;
+
+// The colors of the web UI, see `web/css/styles.css`
+fn palette(year: u32) -> [&'static str; 4] {
+    match year {
+        2021 => ["#3A506B", "#6C22A6", "#5BC0BE", "#6FFFE9"],
+        2022 => ["#0E131F", "#38405F", "#59546C", "#FF0035"],
+        2023 => ["#22092C", "#872341", "#BE3144", "#F05941"],
+        _ => ["#143F6B", "#F55353", "#FEB139", "#F6F54D"],
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Long names would overflow the panels of the card
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(max_chars - 1).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+pub fn render_svg(stats: &Stats) -> String {
+    let [background, panel, accent, highlight] = palette(stats.year);
+    let metrics = [
+        ("WRs written", stats.num_wrs.to_string()),
+        (
+            "got a reply",
+            format!("{:.0}%", stats.ratio_replied_wrs * 100.0),
+        ),
+        (
+            "CC'd the most",
+            stats
+                .top_ccs(1)
+                .pop()
+                .map_or("nobody".to_string(), |(name, _)| name),
+        ),
+        (
+            "favourite weekday",
+            stats.favourite_weekday().map_or("none".to_string(), |day| {
+                stats::weekday_name(day).to_string()
+            }),
+        ),
+    ];
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="DejaVu Sans, Helvetica, Arial, sans-serif">
+<rect width="{WIDTH}" height="{HEIGHT}" fill="{background}"/>
+<circle cx="{WIDTH}" cy="0" r="420" fill="{accent}" opacity="0.6"/>
+<circle cx="0" cy="{HEIGHT}" r="320" fill="{panel}"/>
+<text x="80" y="200" font-size="64" font-weight="bold" fill="white">WRapped</text>
+<text x="80" y="330" font-size="140" font-weight="bold" fill="{highlight}">{}</text>
+"#,
+        stats.year
+    );
+    for (i, (label, value)) in metrics.iter().enumerate() {
+        let y = 430 + i as u32 * 210;
+        svg.push_str(&format!(
+            r#"<rect x="80" y="{y}" width="{}" height="180" rx="24" fill="{panel}" opacity="0.85"/>
+<text x="120" y="{}" font-size="72" font-weight="bold" fill="{highlight}">{}</text>
+<text x="120" y="{}" font-size="36" fill="white">{}</text>
+"#,
+            WIDTH - 160,
+            y + 95,
+            escape_xml(&truncate(value, 22)),
+            y + 150,
+            escape_xml(label)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn render_png(svg: &str) -> Result<Vec<u8>> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    // Without fonts, the text would silently be missing from the card
+    if options.fontdb.is_empty() {
+        return Err(WrError::RenderError(
+            "No fonts found to render the card, install a font such as DejaVu Sans \
+             or write an SVG instead (e.g. `-o wrapped.svg`)"
+                .to_string(),
+        ));
+    }
+    let tree =
+        usvg::Tree::from_str(svg, &options).map_err(|e| WrError::RenderError(e.to_string()))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or(WrError::RenderError("Invalid image size".to_string()))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap
+        .encode_png()
+        .map_err(|e| WrError::RenderError(e.to_string()))
+}
+
+// Write the card as SVG or PNG, depending on the extension of the file
+pub fn write_card(stats: &Stats, path: &str) -> Result<()> {
+    let svg = render_svg(stats);
+    if path.to_lowercase().ends_with(".svg") {
+        fs::write(path, svg)?;
+    } else {
+        fs::write(path, render_png(&svg)?)?;
+    }
+    info!("Wrote the summary card to {}", path);
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, FixedOffset};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::sections::Section;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CarryOver {
+    /// The item as it was first written
+    pub item: String,
+    /// The date of the first WR that contained the item
+    pub first_date: String,
+    /// The date of the last WR that contained the item
+    pub last_date: String,
+    /// The number of calendar weeks from the first to the last WR that contained the item
+    pub weeks: usize,
+}
+
+struct Chain {
+    // The item as it was first written
+    first_item: String,
+    // The item as it was last written
+    last_item: String,
+    first_date: DateTime<FixedOffset>,
+    last_date: DateTime<FixedOffset>,
+    // The index of the last WR that contained the item
+    last_index: usize,
+    // The number of consecutive WRs that contained the item
+    num_wrs: usize,
+}
+
+impl Chain {
+    fn to_carry_over(&self) -> CarryOver {
+        CarryOver {
+            item: self.first_item.clone(),
+            first_date: self.first_date.format("%Y-%m-%d").to_string(),
+            last_date: self.last_date.format("%Y-%m-%d").to_string(),
+            // The first WR already covers the week it was sent in
+            weeks: (self.last_date - self.first_date).num_days() as usize / 7 + 1,
+        }
+    }
+}
+
+fn bigrams(text: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = text
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+// Sørensen-Dice coefficient of the character bigrams of two items
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+pub fn detect_carry_overs(
+    wrs: &[(DateTime<FixedOffset>, &[Section])],
+    section: &str,
+    threshold: f64,
+) -> Vec<CarryOver> {
+    // Walk through the WRs in chronological order
+    let mut order: Vec<usize> = (0..wrs.len()).collect();
+    order.sort_by_key(|&i| wrs[i].0);
+
+    let mut chains: Vec<Chain> = Vec::new();
+    for (index, &i) in order.iter().enumerate() {
+        let (date, sections) = wrs[i];
+        let items = sections
+            .iter()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.items.iter());
+
+        // Only chains that were continued in the previous WR can be extended
+        let mut extended: HashSet<usize> = HashSet::new();
+        for item in items {
+            let best = chains
+                .iter()
+                .enumerate()
+                .filter(|(c, chain)| {
+                    index > 0 && chain.last_index == index - 1 && !extended.contains(c)
+                })
+                .map(|(c, chain)| (c, similarity(&chain.last_item, item)))
+                .filter(|(_, score)| *score >= threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            match best {
+                Some((c, _)) => {
+                    let chain = &mut chains[c];
+                    chain.last_item = item.clone();
+                    chain.last_date = date;
+                    chain.last_index = index;
+                    chain.num_wrs += 1;
+                    extended.insert(c);
+                }
+                None => {
+                    chains.push(Chain {
+                        first_item: item.clone(),
+                        last_item: item.clone(),
+                        first_date: date,
+                        last_date: date,
+                        last_index: index,
+                        num_wrs: 1,
+                    });
+                }
+            }
+        }
+    }
+
+    // Only report items that were carried over at least once, longest-lived first
+    let mut carry_overs: Vec<CarryOver> = chains
+        .iter()
+        .filter(|chain| chain.num_wrs > 1)
+        .map(Chain::to_carry_over)
+        .collect();
+    carry_overs.sort_by(|a, b| b.weeks.cmp(&a.weeks).then(a.first_date.cmp(&b.first_date)));
+    carry_overs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One WR per entry with the date and the items of its "next" section
+    fn carry_overs(wrs: &[(&str, &[&str])]) -> Vec<CarryOver> {
+        let sections: Vec<(DateTime<FixedOffset>, Vec<Section>)> = wrs
+            .iter()
+            .map(|(date, items)| {
+                let section = Section {
+                    name: "next".to_string(),
+                    items: items.iter().map(|item| item.to_string()).collect(),
+                };
+                (DateTime::parse_from_rfc3339(date).unwrap(), vec![section])
+            })
+            .collect();
+        let wrs: Vec<(DateTime<FixedOffset>, &[Section])> = sections
+            .iter()
+            .map(|(date, sections)| (*date, sections.as_slice()))
+            .collect();
+        detect_carry_overs(&wrs, "next", 0.7)
+    }
+
+    #[test]
+    fn similarity_of_items() {
+        assert_eq!(similarity("Write the paper", "write the paper!"), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert_eq!(similarity("", "Write the paper"), 0.0);
+        assert!(similarity("Write the paper", "Write the papers") > 0.9);
+    }
+
+    #[test]
+    fn items_are_chained_over_consecutive_wrs() {
+        let carry_overs = carry_overs(&[
+            (
+                "2023-03-03T17:00:00+01:00",
+                &["Write the paper", "Fix the CI"],
+            ),
+            ("2023-03-10T17:00:00+01:00", &["Write the paper draft"]),
+            ("2023-03-17T17:00:00+01:00", &["Write the paper draft!"]),
+        ]);
+        assert_eq!(carry_overs.len(), 1);
+        assert_eq!(carry_overs[0].item, "Write the paper");
+        assert_eq!(carry_overs[0].first_date, "2023-03-03");
+        assert_eq!(carry_overs[0].last_date, "2023-03-17");
+        assert_eq!(carry_overs[0].weeks, 3);
+    }
+
+    #[test]
+    fn chains_break_when_an_item_is_missing() {
+        let carry_overs = carry_overs(&[
+            ("2023-03-03T17:00:00+01:00", &["Write the paper"]),
+            ("2023-03-10T17:00:00+01:00", &["Fix the CI"]),
+            ("2023-03-17T17:00:00+01:00", &["Write the paper"]),
+            ("2023-03-24T17:00:00+01:00", &["Write the paper"]),
+        ]);
+        assert_eq!(carry_overs.len(), 1);
+        assert_eq!(carry_overs[0].first_date, "2023-03-17");
+        assert_eq!(carry_overs[0].weeks, 2);
+    }
+
+    #[test]
+    fn weeks_without_a_wr_are_counted() {
+        // The item is carried over between consecutive WRs that are two weeks apart
+        let carry_overs = carry_overs(&[
+            ("2023-03-03T17:00:00+01:00", &["Write the paper"]),
+            ("2023-03-17T17:00:00+01:00", &["Write the paper"]),
+        ]);
+        assert_eq!(carry_overs.len(), 1);
+        assert_eq!(carry_overs[0].weeks, 3);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
@@ -6,6 +8,39 @@ pub struct MailConfig {
     pub server: MailLogin,
     // The fetch configuration
     pub query: MailQuery,
+    // The text analysis configuration
+    #[serde(default)]
+    pub text: TextConfig,
+    // The sections of the WR template, mapped to the headings that start them
+    #[serde(default = "default_sections")]
+    pub sections: BTreeMap<String, Vec<String>>,
+    // The configuration of the carry-over detection of TODO items
+    #[serde(default)]
+    pub carry_over: CarryOverConfig,
+    // The projects to tag the WRs with
+    #[serde(default)]
+    pub projects: BTreeMap<String, ProjectConfig>,
+    // The thresholds of the achievements
+    #[serde(default)]
+    pub achievements: AchievementConfig,
+    // Alternative addresses mapped to the canonical address of the same person
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+}
+
+fn default_sections() -> BTreeMap<String, Vec<String>> {
+    let sections = [
+        ("done", vec!["Done", "This week", "Progress"]),
+        ("next", vec!["Next", "Next week", "Plans", "Todo"]),
+        ("blockers", vec!["Blockers", "Problems", "Issues"]),
+    ];
+    sections
+        .into_iter()
+        .map(|(name, headings)| {
+            let headings = headings.into_iter().map(|h| h.to_string()).collect();
+            (name.to_string(), headings)
+        })
+        .collect()
 }
 
 #[derive(Deserialize, Debug)]
@@ -35,3 +70,128 @@ pub struct MailQuery {
     // The year to fetch the WRs from
     pub year: u32,
 }
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TextConfig {
+    // The languages of the built-in stopword lists ("en", "de")
+    #[serde(default = "default_stopword_languages")]
+    pub stopword_languages: Vec<String>,
+    // Additional words to ignore in the text analysis
+    #[serde(default)]
+    pub stopwords: Vec<String>,
+    // The minimum number of characters of a word
+    #[serde(default = "default_min_word_length")]
+    pub min_word_length: usize,
+    // The number of top words and keywords to export
+    #[serde(default = "default_num_top_words")]
+    pub num_top_words: usize,
+}
+
+fn default_stopword_languages() -> Vec<String> {
+    vec!["en".to_string(), "de".to_string()]
+}
+
+fn default_min_word_length() -> usize {
+    3
+}
+
+fn default_num_top_words() -> usize {
+    30
+}
+
+impl Default for TextConfig {
+    fn default() -> Self {
+        TextConfig {
+            stopword_languages: default_stopword_languages(),
+            stopwords: Vec::new(),
+            min_word_length: default_min_word_length(),
+            num_top_words: default_num_top_words(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CarryOverConfig {
+    // The section whose items are tracked over consecutive WRs
+    #[serde(default = "default_carry_over_section")]
+    pub section: String,
+    // The minimum similarity (0 to 1) for two items to be considered the same
+    #[serde(default = "default_carry_over_similarity")]
+    pub similarity: f64,
+}
+
+fn default_carry_over_section() -> String {
+    "next".to_string()
+}
+
+fn default_carry_over_similarity() -> f64 {
+    0.7
+}
+
+impl Default for CarryOverConfig {
+    fn default() -> Self {
+        CarryOverConfig {
+            section: default_carry_over_section(),
+            similarity: default_carry_over_similarity(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProjectConfig {
+    // The keywords that mark a WR as mentioning the project
+    pub keywords: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AchievementConfig {
+    // WRs sent from this hour on, but before `early_bird_hour`, earn the "Early Bird" badge
+    pub early_bird_start_hour: u32,
+    // WRs sent before this hour earn the "Early Bird" badge
+    pub early_bird_hour: u32,
+    // WRs sent at or after this hour earn the "Night Owl" badge
+    pub night_owl_hour: u32,
+    // A WR with more words earns the "Novelist" badge
+    pub novelist_words: usize,
+    // WRs without a reply for this many weeks in a row earn the "Ghosted" badge
+    pub ghosted_weeks: usize,
+}
+
+impl Default for AchievementConfig {
+    fn default() -> Self {
+        AchievementConfig {
+            early_bird_start_hour: 5,
+            early_bird_hour: 8,
+            night_owl_hour: 22,
+            novelist_words: 1000,
+            ghosted_weeks: 4,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // A configuration with the defaults for everything but the query
+    pub(crate) fn test_config(from: &str) -> MailConfig {
+        let config = format!(
+            r#"
+            [server]
+            server = "mail.example.com"
+            port = 993
+
+            [query]
+            wr_mailboxes = ["Sent"]
+            re_mailboxes = ["INBOX"]
+            pattern = ["WR"]
+            from = "{}"
+            to = "boss@example.com"
+            year = 2023
+            "#,
+            from
+        );
+        toml::from_str(&config).unwrap()
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use chrono::DateTime;
+use log::info;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use crate::error::{Result, WrError};
+use crate::mail::{Address, Attachment, Envelope, Mail};
+use crate::wr::{WRs, WR};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS mails (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    -- Whether the mail is a WR that was sent or a reply that was received
+    kind TEXT NOT NULL,
+    -- The Message-ID, or the date and subject if the mail has none
+    key TEXT NOT NULL,
+    imap_id INTEGER NOT NULL,
+    date TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    message_id TEXT,
+    in_reply_to TEXT,
+    body TEXT,
+    UNIQUE (kind, key)
+);
+CREATE TABLE IF NOT EXISTS addresses (
+    mail_id INTEGER NOT NULL REFERENCES mails (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    name TEXT,
+    user TEXT,
+    email TEXT
+);
+CREATE TABLE IF NOT EXISTS attachments (
+    mail_id INTEGER NOT NULL REFERENCES mails (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    filename TEXT,
+    mimetype TEXT NOT NULL,
+    size INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS wrs (
+    sent_id INTEGER PRIMARY KEY REFERENCES mails (id) ON DELETE CASCADE,
+    reply_id INTEGER REFERENCES mails (id) ON DELETE SET NULL,
+    year INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS addresses_mail ON addresses (mail_id);
+CREATE INDEX IF NOT EXISTS attachments_mail ON attachments (mail_id);
+CREATE INDEX IF NOT EXISTS wrs_year ON wrs (year);
+";
+
+pub struct Database {
+    conn: Connection,
+}
+
+// Identify a mail across syncs, even if the IMAP id changed
+fn mail_key(mail: &Mail) -> String {
+    match mail.env.message_id.as_ref() {
+        Some(message_id) => message_id.clone(),
+        None => format!("{}|{}", mail.env.date.to_rfc3339(), mail.env.subject),
+    }
+}
+
+impl Database {
+    pub fn open(path: &str) -> Result<Self> {
+        if let Some(dir_path) = Path::new(path).parent() {
+            fs::create_dir_all(dir_path)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Database { conn })
+    }
+
+    // Insert or update a mail and return its id in the database
+    fn store_mail(tx: &Transaction, kind: &str, mail: &Mail) -> Result<i64> {
+        let id: i64 = tx.query_row(
+            "INSERT INTO mails (kind, key, imap_id, date, subject, message_id, in_reply_to, body)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (kind, key) DO UPDATE SET
+                imap_id = excluded.imap_id,
+                date = excluded.date,
+                subject = excluded.subject,
+                in_reply_to = excluded.in_reply_to,
+                body = excluded.body
+             RETURNING id",
+            params![
+                kind,
+                mail_key(mail),
+                mail.id,
+                mail.env.date.to_rfc3339(),
+                mail.env.subject,
+                mail.env.message_id,
+                mail.env.in_reply_to,
+                mail.body,
+            ],
+            |row| row.get(0),
+        )?;
+
+        // The addresses and attachments are replaced as a whole
+        tx.execute("DELETE FROM addresses WHERE mail_id = ?1", params![id])?;
+        tx.execute("DELETE FROM attachments WHERE mail_id = ?1", params![id])?;
+        for (position, address) in mail.env.cc.iter().flatten().enumerate() {
+            tx.execute(
+                "INSERT INTO addresses (mail_id, position, name, user, email)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, position, address.name, address.user, address.email],
+            )?;
+        }
+        for (position, attachment) in mail.attachments.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO attachments (mail_id, position, filename, mimetype, size)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    id,
+                    position,
+                    attachment.filename,
+                    attachment.mimetype,
+                    attachment.size
+                ],
+            )?;
+        }
+        Ok(id)
+    }
+
+    // Store the WRs of a year with their replies, replacing what was stored before
+    pub fn store_wrs(&mut self, wrs: &WRs, year: u32) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let mut sent_ids = HashSet::new();
+        for wr in wrs.wrs.iter() {
+            let sent_id = Self::store_mail(&tx, "sent", &wr.sent)?;
+            sent_ids.insert(sent_id);
+            let reply_id = match wr.reply.as_ref() {
+                Some(reply) => Some(Self::store_mail(&tx, "reply", reply)?),
+                None => None,
+            };
+            tx.execute(
+                "INSERT INTO wrs (sent_id, reply_id, year) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (sent_id) DO UPDATE SET reply_id = excluded.reply_id, year = excluded.year",
+                params![sent_id, reply_id, year],
+            )?;
+        }
+
+        // WRs that were deleted or no longer match the query are removed with their CCs
+        // and attachments, as well as the replies that no WR refers to anymore
+        let stored_ids = tx
+            .prepare("SELECT sent_id FROM wrs WHERE year = ?1")?
+            .query_map(params![year], |row| row.get::<_, i64>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut num_removed = 0;
+        for id in stored_ids.into_iter().filter(|id| !sent_ids.contains(id)) {
+            num_removed += tx.execute("DELETE FROM mails WHERE id = ?1", params![id])?;
+        }
+        tx.execute(
+            "DELETE FROM mails WHERE kind = 'reply'
+             AND id NOT IN (SELECT reply_id FROM wrs WHERE reply_id IS NOT NULL)",
+            [],
+        )?;
+        tx.commit()?;
+        if num_removed > 0 {
+            info!("Removed {} WRs of {} from the database", num_removed, year);
+        }
+        info!("Stored {} WRs of {} in the database", wrs.num_wrs(), year);
+        Ok(())
+    }
+
+    fn load_addresses(&self, mail_id: i64) -> Result<Vec<Address>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT name, user, email FROM addresses WHERE mail_id = ?1 ORDER BY position",
+        )?;
+        let addresses = stmt
+            .query_map(params![mail_id], |row| {
+                Ok(Address {
+                    name: row.get(0)?,
+                    user: row.get(1)?,
+                    email: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(addresses)
+    }
+
+    fn load_attachments(&self, mail_id: i64) -> Result<Vec<Attachment>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT filename, mimetype, size FROM attachments WHERE mail_id = ?1 ORDER BY position",
+        )?;
+        let attachments = stmt
+            .query_map(params![mail_id], |row| {
+                Ok(Attachment {
+                    filename: row.get(0)?,
+                    mimetype: row.get(1)?,
+                    size: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(attachments)
+    }
+
+    fn load_mail(&self, mail_id: i64) -> Result<Mail> {
+        let (imap_id, date, subject, message_id, in_reply_to, body): (
+            u32,
+            String,
+            String,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        ) = self.conn.query_row(
+            "SELECT imap_id, date, subject, message_id, in_reply_to, body
+             FROM mails WHERE id = ?1",
+            params![mail_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )?;
+        let date = DateTime::parse_from_rfc3339(&date)
+            .map_err(|e| WrError::DatabaseError(format!("Invalid date {}: {}", date, e)))?;
+        let cc = self.load_addresses(mail_id)?;
+        Ok(Mail {
+            id: imap_id,
+            env: Envelope {
+                date,
+                subject,
+                cc: (!cc.is_empty()).then_some(cc),
+                in_reply_to,
+                message_id,
+            },
+            body,
+            attachments: self.load_attachments(mail_id)?,
+        })
+    }
+
+    // Load the WRs of a year with their replies
+    pub fn load_wrs(&self, year: u32) -> Result<WRs> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT sent_id, reply_id FROM wrs WHERE year = ?1 ORDER BY sent_id")?;
+        let ids = stmt
+            .query_map(params![year], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut wrs = WRs::new();
+        for (sent_id, reply_id) in ids {
+            let reply = match reply_id {
+                Some(reply_id) => Some(self.load_mail(reply_id)?),
+                None => None,
+            };
+            wrs.wrs.push(WR::new(self.load_mail(sent_id)?, reply));
+        }
+        info!("Loaded {} WRs of {} from the database", wrs.num_wrs(), year);
+        Ok(wrs)
+    }
+
+    // The years in the database with their number of WRs
+    pub fn years(&self) -> Result<Vec<(u32, usize)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT year, COUNT(*) FROM wrs GROUP BY year ORDER BY year")?;
+        let years = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(years)
+    }
+
+    // Whether a year was synced before
+    pub fn has_year(&self, year: u32) -> Result<bool> {
+        let found = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM wrs WHERE year = ?1 LIMIT 1",
+                params![year],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+}
@@ -18,6 +18,10 @@ pub enum WrError {
     ServerError(String),
     // Mail parsing error
     MailParseError(String),
+    // Error while rendering an image
+    RenderError(String),
+    // Error from the SQLite database
+    DatabaseError(String),
 }
 
 impl std::fmt::Display for WrError {
@@ -30,6 +34,8 @@ impl std::fmt::Display for WrError {
             WrError::SerializationError(e) => write!(f, "Serialization error: {}", e),
             WrError::ServerError(e) => write!(f, "Server error: {}", e),
             WrError::MailParseError(e) => write!(f, "Mail parse error: {}", e),
+            WrError::RenderError(e) => write!(f, "Render error: {}", e),
+            WrError::DatabaseError(e) => write!(f, "Database error: {}", e),
         }
     }
 }
@@ -58,4 +64,10 @@ impl From<mailparse::MailParseError> for WrError {
     }
 }
 
+impl From<rusqlite::Error> for WrError {
+    fn from(error: rusqlite::Error) -> Self {
+        WrError::DatabaseError(error.to_string())
+    }
+}
+
 impl std::error::Error for WrError {}
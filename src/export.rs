@@ -0,0 +1,97 @@
+use std::fs;
+
+use log::info;
+
+use crate::assets::Assets;
+use crate::error::{Result, WrError};
+use crate::stats::Stats;
+
+// Find the value of an attribute such as `src="js/main.js"` in a tag
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!("{}=\"", name))? + name.len() + 2;
+    let end = tag[start..].find('"')? + start;
+    Some(&tag[start..end])
+}
+
+// Local assets are inlined, assets from a CDN are kept as they are
+fn is_local(path: &str) -> bool {
+    !path.starts_with("http://") && !path.starts_with("https://") && !path.starts_with("//")
+}
+
+// Inline scripts must not close the surrounding <script> tag
+fn escape_script(script: &str) -> String {
+    script.replace("</script", "<\\/script")
+}
+
+fn read_asset(assets: &Assets, path: &str) -> Result<String> {
+    assets.get_string(path).ok_or_else(|| {
+        WrError::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Could not read asset {}", path),
+        ))
+    })
+}
+
+fn inline_assets(html: &str, assets: &Assets, data_script: &str) -> Result<String> {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut data_script = Some(data_script);
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find('>') {
+            Some(end) => end + 1,
+            None => break,
+        };
+        let tag = &rest[..end];
+
+        if tag.starts_with("<link") && attribute(tag, "rel") == Some("stylesheet") {
+            match attribute(tag, "href").filter(|href| is_local(href)) {
+                Some(href) => {
+                    let css = read_asset(assets, href)?;
+                    output.push_str(&format!("<style>\n{}\n</style>", css));
+                }
+                None => output.push_str(tag),
+            }
+            rest = &rest[end..];
+        } else if tag.starts_with("<script") {
+            match attribute(tag, "src").filter(|src| is_local(src)) {
+                Some(src) => {
+                    let script = read_asset(assets, src)?;
+                    // The data has to be defined before the script that reads it
+                    if let Some(data_script) = data_script.take() {
+                        output.push_str(data_script);
+                    }
+                    output.push_str(&format!("<script>\n{}\n</script>", escape_script(&script)));
+                    // Skip the closing tag of the original script
+                    let close = rest
+                        .find("</script>")
+                        .map_or(end, |i| i + "</script>".len());
+                    rest = &rest[close..];
+                }
+                None => {
+                    output.push_str(tag);
+                    rest = &rest[end..];
+                }
+            }
+        } else {
+            output.push_str(tag);
+            rest = &rest[end..];
+        }
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+pub fn export_html(stats: &Stats, assets: &Assets, output_file: &str) -> Result<()> {
+    let stats = serde_json::to_string(stats)?;
+    let html = read_asset(assets, "index.html")?;
+    let data_script = format!(
+        "<script>\nwindow.WRAPPED_STATS = {};\n</script>\n",
+        escape_script(&stats)
+    );
+    let output = inline_assets(&html, assets, &data_script)?;
+    fs::write(output_file, output)?;
+    info!("Exported the WRapped to {}", output_file);
+    Ok(())
+}
@@ -7,6 +7,7 @@ use imap::ImapConnection;
 use itertools::join;
 use log::debug;
 use log::{info, warn};
+use std::collections::BTreeMap;
 use std::str::from_utf8;
 
 use crate::config::{MailConfig, MailLogin, MailQuery};
@@ -19,13 +20,29 @@ pub struct Address {
     pub email: Option<String>,
 }
 
+// Decode MIME encoded-words such as "=?UTF-8?Q?J=C3=BCrg?=" in a header value
+fn decode_header_value(value: &[u8]) -> String {
+    let header = [b"X: ", value].concat();
+    match mailparse::parse_header(&header) {
+        Ok((header, _)) => header.get_value(),
+        Err(_) => String::from_utf8_lossy(value).to_string(),
+    }
+}
+
+// The canonical address of the person an address belongs to
+pub fn canonical_email(email: &str, aliases: &BTreeMap<String, String>) -> String {
+    let email = email.trim().to_lowercase();
+    aliases
+        .iter()
+        .find(|(alias, _)| alias.to_lowercase() == email)
+        .map(|(_, person)| person.trim().to_lowercase())
+        .unwrap_or(email)
+}
+
 impl Address {
     pub fn from_imap_address(addr: &imap_proto::types::Address) -> Self {
         Address {
-            name: addr
-                .name
-                .as_ref()
-                .map(|s| String::from_utf8_lossy(s).to_string()),
+            name: addr.name.as_ref().map(|s| decode_header_value(s)),
             user: addr
                 .mailbox
                 .as_ref()
@@ -46,6 +63,20 @@ impl Address {
             },
         }
     }
+
+    // The lowercase email address, used to identify a person
+    pub fn normalized_email(&self) -> Option<String> {
+        self.email.as_ref().map(|email| email.trim().to_lowercase())
+    }
+
+    // Replace the address with the canonical address of the person
+    pub fn apply_aliases(&mut self, aliases: &BTreeMap<String, String>) {
+        if let Some(email) = self.email.as_ref() {
+            let canonical = canonical_email(email, aliases);
+            self.user = canonical.split('@').next().map(|user| user.to_string());
+            self.email = Some(canonical);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -85,11 +116,19 @@ impl Envelope {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub filename: Option<String>,
+    pub mimetype: String,
+    pub size: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct Mail {
     pub id: u32,
     pub env: Envelope,
     pub body: Option<String>,
+    pub attachments: Vec<Attachment>,
 }
 
 fn imap_login(login: &MailLogin) -> Result<imap::Session<Box<dyn ImapConnection>>> {
@@ -149,7 +188,32 @@ pub fn fetch_inbox(config: &MailConfig) -> Result<()> {
     Ok(())
 }
 
-fn build_imap_search_query(fetch: &MailQuery) -> Result<String> {
+// Search key that matches an address or any of the addresses of the same person
+fn address_query(key: &str, address: &str, aliases: &BTreeMap<String, String>) -> String {
+    let canonical = canonical_email(address, aliases);
+    let mut addresses = vec![address.to_string()];
+    for candidate in aliases.iter().flat_map(|(alias, person)| [alias, person]) {
+        let known = addresses.iter().any(|a| a.eq_ignore_ascii_case(candidate));
+        if !known && canonical_email(candidate, aliases) == canonical {
+            addresses.push(candidate.clone());
+        }
+    }
+
+    // IMAP uses prefix notation for OR, e.g. (OR OR FROM "a" FROM "b" FROM "c")
+    let keys: Vec<String> = addresses
+        .iter()
+        .map(|a| format!("{} \"{}\"", key, a))
+        .collect();
+    match keys.len() {
+        1 => keys[0].clone(),
+        n => format!("({}{})", "OR ".repeat(n - 1), keys.join(" ")),
+    }
+}
+
+fn build_imap_search_query(
+    fetch: &MailQuery,
+    aliases: &BTreeMap<String, String>,
+) -> Result<String> {
     // Check that patterns is not empty
     if fetch.pattern.is_empty() {
         return Err(WrError::QueryError("No pattern specified".to_string()));
@@ -172,9 +236,9 @@ fn build_imap_search_query(fetch: &MailQuery) -> Result<String> {
         _ => unreachable!(),
     };
 
-    // Format the from, to and year of the query
-    query = format!("{} FROM \"{}\"", query, fetch.from);
-    query = format!("{} TO \"{}\"", query, fetch.to);
+    // Format the from, to (including their aliases) and year of the query
+    query = format!("{} {}", query, address_query("FROM", &fetch.from, aliases));
+    query = format!("{} {}", query, address_query("TO", &fetch.to, aliases));
     query = format!("{} SINCE \"01-Jan-{}\"", query, fetch.year);
     query = format!("{} BEFORE \"01-Jan-{}\"", query, fetch.year + 1);
 
@@ -201,12 +265,42 @@ fn get_plain_text(mail: &mailparse::ParsedMail) -> Result<String> {
     Ok(body_str)
 }
 
+fn get_attachments(mail: &mailparse::ParsedMail) -> Vec<Attachment> {
+    let mut attachments = Vec::new();
+    for part in mail.parts().filter(|part| part.subparts.is_empty()) {
+        let disposition = part.get_content_disposition();
+        let filename = disposition
+            .params
+            .get("filename")
+            .or(part.ctype.params.get("name"))
+            .cloned();
+
+        // Parts that are explicitly attached or named, but not the text of the mail itself
+        let is_attachment = match disposition.disposition {
+            mailparse::DispositionType::Attachment => true,
+            _ => filename.is_some() && !part.ctype.mimetype.starts_with("text/"),
+        };
+        if !is_attachment {
+            continue;
+        }
+
+        // Only the size of the decoded content is kept
+        let size = part.get_body_raw().map(|body| body.len()).unwrap_or(0);
+        attachments.push(Attachment {
+            filename,
+            mimetype: part.ctype.mimetype.to_lowercase(),
+            size,
+        });
+    }
+    attachments
+}
+
 pub fn fetch_wrs(config: &MailConfig) -> Result<Vec<Mail>> {
     // Login to the IMAP server
     let mut imap_session = imap_login(&config.server)?;
 
     // Search for messages that contain the pattern
-    let query = build_imap_search_query(&config.query)?;
+    let query = build_imap_search_query(&config.query, &config.aliases)?;
 
     // List of WRs
     let mut wrs = Vec::new();
@@ -245,6 +339,7 @@ pub fn fetch_wrs(config: &MailConfig) -> Result<Vec<Mail>> {
                         id: message.message,
                         env,
                         body: None,
+                        attachments: Vec::new(),
                     });
                 }
                 Some(_) => {
@@ -259,6 +354,7 @@ pub fn fetch_wrs(config: &MailConfig) -> Result<Vec<Mail>> {
                         id: message.message,
                         env,
                         body: None,
+                        attachments: Vec::new(),
                     });
                 }
             };
@@ -279,6 +375,7 @@ pub fn fetch_wrs(config: &MailConfig) -> Result<Vec<Mail>> {
             let body = message.body().unwrap();
             let parsed_mail = mailparse::parse_mail(body)?;
             wr.body = get_plain_text(&parsed_mail).ok(); // If an error occurs, just skip the body
+            wr.attachments = get_attachments(&parsed_mail);
         }
     }
 
@@ -295,7 +392,7 @@ pub fn fetch_replies(config: &MailConfig) -> Result<Vec<Mail>> {
     std::mem::swap(&mut reply_fetch.from, &mut reply_fetch.to);
 
     // Search for messages that contain the pattern
-    let query = build_imap_search_query(&reply_fetch)?;
+    let query = build_imap_search_query(&reply_fetch, &config.aliases)?;
 
     // List of WRs
     let mut wr_replies = Vec::new();
@@ -321,21 +418,50 @@ pub fn fetch_replies(config: &MailConfig) -> Result<Vec<Mail>> {
         let messages = imap_session.fetch(sequence_set, "ENVELOPE")?;
 
         // Print the subjects of the messages
+        let mut mailbox_replies = Vec::new();
         for message in messages.iter() {
             let envelope = message.envelope().unwrap();
 
             match envelope.in_reply_to {
                 Some(_) => {
                     let env = Envelope::from_imap_envelope(envelope);
-                    wr_replies.push(Mail {
+                    mailbox_replies.push(Mail {
                         id: message.message,
                         env,
                         body: None,
+                        attachments: Vec::new(),
                     });
                 }
                 None => continue,
             };
         }
+
+        if mailbox_replies.is_empty() {
+            continue;
+        }
+
+        // Construct a new sequence set of all the replies
+        let sequence_set: Vec<u32> = mailbox_replies.iter().map(|m: &Mail| m.id).collect();
+        let sequence_set: String = join(sequence_set.into_iter().map(|s| s.to_string()), ",");
+
+        // Fetch the bodies of the replies
+        info!(
+            "Fetching bodies of {} potential Replies",
+            mailbox_replies.len()
+        );
+        let messages = imap_session.fetch(sequence_set, "BODY[]")?;
+
+        // Add the text of the body to the replies with the same sequence number
+        for message in messages.iter() {
+            let reply = mailbox_replies.iter_mut().find(|m| m.id == message.message);
+            if let (Some(reply), Some(body)) = (reply, message.body()) {
+                let parsed_mail = mailparse::parse_mail(body)?;
+                reply.body = get_plain_text(&parsed_mail).ok(); // If an error occurs, just skip the body
+                reply.attachments = get_attachments(&parsed_mail);
+            }
+        }
+
+        wr_replies.extend(mailbox_replies);
     }
 
     info!("Found {} potential Replies", wr_replies.len());
@@ -343,3 +469,43 @@ pub fn fetch_replies(config: &MailConfig) -> Result<Vec<Mail>> {
     imap_session.logout()?;
     Ok(wr_replies)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("old@uni.ch".to_string(), "me@lab.ch".to_string()),
+            ("older@ethz.ch".to_string(), "me@lab.ch".to_string()),
+        ])
+    }
+
+    #[test]
+    fn canonical_email_resolves_aliases() {
+        assert_eq!(canonical_email(" Old@Uni.CH ", &aliases()), "me@lab.ch");
+        assert_eq!(canonical_email("me@lab.ch", &aliases()), "me@lab.ch");
+        assert_eq!(canonical_email("Boss@Lab.ch", &aliases()), "boss@lab.ch");
+    }
+
+    #[test]
+    fn address_query_without_aliases() {
+        assert_eq!(
+            address_query("FROM", "boss@lab.ch", &aliases()),
+            "FROM \"boss@lab.ch\""
+        );
+    }
+
+    #[test]
+    fn address_query_combines_all_addresses_of_a_person() {
+        assert_eq!(
+            address_query("FROM", "me@lab.ch", &aliases()),
+            "(OR OR FROM \"me@lab.ch\" FROM \"old@uni.ch\" FROM \"older@ethz.ch\")"
+        );
+        // Starting from an alias finds the canonical address and the other alias
+        assert_eq!(
+            address_query("TO", "OLD@uni.ch", &aliases()),
+            "(OR OR TO \"OLD@uni.ch\" TO \"me@lab.ch\" TO \"older@ethz.ch\")"
+        );
+    }
+}
@@ -1,13 +1,30 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
+use std::path::Path;
 
-use clap::Command;
+use clap::{Arg, ArgAction, ArgMatches, Command};
 
+pub mod achievements;
+pub mod assets;
+pub mod card;
+pub mod carryover;
 pub mod config;
+pub mod db;
 pub mod error;
+pub mod export;
 pub mod mail;
+pub mod privacy;
+pub mod records;
+pub mod report;
+pub mod sections;
+pub mod sentiment;
 pub mod server;
 pub mod stats;
+pub mod style;
+pub mod team;
+pub mod text;
+pub mod tui;
 pub mod wr;
 
 use error::{Result, WrError};
@@ -16,22 +33,188 @@ fn cli() -> Command {
     Command::new("WRapped")
         .about("Wrapped but for Weekly Reports")
         .allow_external_subcommands(true)
+        .arg(
+            Arg::new("anonymize")
+                .long("anonymize")
+                .help("Replace names and addresses with pseudonyms and drop text from the WRs")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("web-dir")
+                .long("web-dir")
+                .help("Serve the frontend from this directory instead of the built-in one")
+                .global(true),
+        )
+        .arg(
+            Arg::new("salt")
+                .long("salt")
+                .help("The secret used to derive stable pseudonyms with --anonymize")
+                .global(true),
+        )
         .subcommand(Command::new("mailboxes").about("List mailboxes"))
         .subcommand(Command::new("fetch-inbox").about("Fetch the first mail in the inbox"))
         .subcommand(Command::new("fetch-wrs").about("Fetch all WRs"))
         .subcommand(Command::new("fetch-replies").about("Fetch all the replies of the WRs"))
+        .subcommand(
+            Command::new("export-html")
+                .about("Export the stats into a single self-contained HTML file")
+                .arg(
+                    Arg::new("stats")
+                        .long("stats")
+                        .help("The stats file to export")
+                        .default_value("shared/stats.json"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("The HTML file to write")
+                        .default_value("wrapped.html"),
+                ),
+        )
+        .subcommand(
+            Command::new("card")
+                .about("Render a summary card of the stats as PNG or SVG")
+                .arg(
+                    Arg::new("stats")
+                        .long("stats")
+                        .help("The stats file to render")
+                        .default_value("shared/stats.json"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("The image to write, ending in .png or .svg")
+                        .default_value("wrapped.png"),
+                ),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("Print the stats as a Markdown or plain-text report")
+                .arg(
+                    Arg::new("stats")
+                        .long("stats")
+                        .help("The stats file to report")
+                        .default_value("shared/stats.json"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .help("The format of the report")
+                        .value_parser(clap::value_parser!(report::Format))
+                        .default_value("markdown"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("The file to write the report to instead of stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export one record per WR as CSV or JSON Lines")
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .help("The format of the records")
+                        .value_parser(clap::value_parser!(records::Format))
+                        .default_value("csv"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("The file to write the records to instead of stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("tui")
+                .about("Browse the stats in the terminal")
+                .arg(
+                    Arg::new("stats")
+                        .long("stats")
+                        .help("The stats file to browse")
+                        .default_value("shared/stats.json"),
+                ),
+        )
+        .subcommand(
+            Command::new("db")
+                .about("Store the WRs in a local database and compute stats from it")
+                .subcommand_required(true)
+                .arg(
+                    Arg::new("db")
+                        .long("db")
+                        .help("The SQLite database file")
+                        .default_value("wrapped.db")
+                        .global(true),
+                )
+                .subcommand(
+                    Command::new("sync").about("Fetch the WRs of the configured year into the database"),
+                )
+                .subcommand(
+                    Command::new("stats")
+                        .about("Compute the stats from the database without fetching mails")
+                        .arg(
+                            Arg::new("year")
+                                .long("year")
+                                .help("The year to compute the stats for, defaults to the configured year")
+                                .value_parser(clap::value_parser!(u32)),
+                        ),
+                )
+                .subcommand(Command::new("years").about("List the years in the database")),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Print the JSON Schema of the stats file")
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("The file to write the schema to instead of stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serve existing stats without fetching mails")
+                .arg(
+                    Arg::new("stats")
+                        .help("A stats file or a directory with the stats files of several years")
+                        .default_value("shared/stats.json"),
+                )
+                .arg(
+                    Arg::new("db")
+                        .long("db")
+                        .help("A database from `db sync` to serve the individual WRs from"),
+                ),
+        )
+        .subcommand(
+            Command::new("team")
+                .about("Aggregate the stats of several team members")
+                .arg(
+                    Arg::new("stats")
+                        .help("The stats.json files of the team members")
+                        .num_args(1..)
+                        .required(true),
+                ),
+        )
 }
 
-#[actix_web::main]
-async fn main() -> Result<()> {
-    env::set_var("RUST_LOG", "actix_server=warn,info");
-    pretty_env_logger::init();
-
+// Read the config without asking for credentials
+fn load_config() -> Result<config::MailConfig> {
     let config_contents = fs::read_to_string("config.toml")
         .map_err(|_| WrError::ConfigError("Could not read config file".to_string()))?;
 
-    let mut mail_config: config::MailConfig = toml::from_str(&config_contents)
-        .map_err(|_| WrError::ConfigError("Could not parse config file".to_string()))?;
+    toml::from_str(&config_contents)
+        .map_err(|_| WrError::ConfigError("Could not parse config file".to_string()))
+}
+
+fn read_config() -> Result<config::MailConfig> {
+    let mut mail_config = load_config()?;
 
     let username = match mail_config.server.username {
         Some(username) => Some(username),
@@ -51,23 +234,246 @@ async fn main() -> Result<()> {
     mail_config.server.username = username;
     mail_config.server.password = password;
 
-    let matches = cli().get_matches();
+    Ok(mail_config)
+}
+
+// Fetch the WRs and their replies and merge them
+fn fetch_merged_wrs(mail_config: &config::MailConfig) -> Result<wr::WRs> {
+    let wrs = mail::fetch_wrs(mail_config)?;
+    let replies = mail::fetch_replies(mail_config)?;
+    let mut merged_wrs = wr::merge_wrs(&wrs, &replies);
+    merged_wrs.apply_aliases(&mail_config.aliases);
+    merged_wrs.tag_projects(&mail_config.projects);
+    Ok(merged_wrs)
+}
+
+// The frontend assets, from `--web-dir` if given
+fn assets(matches: &ArgMatches) -> assets::Assets {
+    assets::Assets::new(matches.get_one::<String>("web-dir").map(|s| s.as_str()))
+}
+
+async fn serve(page: &str, data: server::ServerData, assets: assets::Assets) -> Result<()> {
+    let localhost = "127.0.0.1:8080";
+    let url = format!("http://{}/{}", localhost, page);
+    server::open_browser(&url);
+    server::run_server(localhost, data, assets).await?;
+    Ok(())
+}
+
+// The records of the WRs, anonymized if a pseudonymizer is given
+fn wr_records(
+    wrs: &wr::WRs,
+    pseudonymizer: Option<&privacy::Pseudonymizer>,
+) -> Vec<records::WrRecord> {
+    let mut records = records::records(wrs);
+    if let Some(pseudonymizer) = pseudonymizer {
+        records
+            .iter_mut()
+            .for_each(|record| record.anonymize(pseudonymizer));
+    }
+    records
+}
+
+// Write the stats to `shared/stats.json` and serve them with their WRs
+async fn serve_stats(mut stats: stats::Stats, wrs: &wr::WRs, matches: &ArgMatches) -> Result<()> {
+    // The stats and the WRs share the salt for the pseudonyms to match
+    let pseudonymizer = pseudonymizer(matches);
+    if let Some(pseudonymizer) = pseudonymizer.as_ref() {
+        pseudonymizer.anonymize(&mut stats);
+    }
+    stats.write_to_file("shared/stats.json")?;
+    let data = server::ServerData {
+        wr_records: BTreeMap::from([(stats.year, wr_records(wrs, pseudonymizer.as_ref()))]),
+        stats: BTreeMap::from([(stats.year, stats)]),
+        team: None,
+    };
+    serve("", data, assets(matches)).await
+}
+
+// The pseudonymizer to apply to the stats, if `--anonymize` was given
+fn pseudonymizer(matches: &ArgMatches) -> Option<privacy::Pseudonymizer> {
+    if !matches.get_flag("anonymize") {
+        return None;
+    }
+    let salt = matches.get_one::<String>("salt").map(|s| s.as_str());
+    Some(privacy::Pseudonymizer::new(salt))
+}
+
+fn run_export_html(matches: &ArgMatches) -> Result<()> {
+    let mut stats = stats::Stats::read_from_file(matches.get_one::<String>("stats").unwrap())?;
+    if let Some(pseudonymizer) = pseudonymizer(matches) {
+        pseudonymizer.anonymize(&mut stats);
+    }
+    export::export_html(
+        &stats,
+        &assets(matches),
+        matches.get_one::<String>("output").unwrap(),
+    )
+}
+
+fn run_card(matches: &ArgMatches) -> Result<()> {
+    let mut stats = stats::Stats::read_from_file(matches.get_one::<String>("stats").unwrap())?;
+    if let Some(pseudonymizer) = pseudonymizer(matches) {
+        pseudonymizer.anonymize(&mut stats);
+    }
+    card::write_card(&stats, matches.get_one::<String>("output").unwrap())
+}
+
+fn run_report(matches: &ArgMatches) -> Result<()> {
+    let mut stats = stats::Stats::read_from_file(matches.get_one::<String>("stats").unwrap())?;
+    if let Some(pseudonymizer) = pseudonymizer(matches) {
+        pseudonymizer.anonymize(&mut stats);
+    }
+    let format = *matches.get_one::<report::Format>("format").unwrap();
+    let output = matches.get_one::<String>("output").map(|s| s.as_str());
+    report::write_report(&stats, format, output)
+}
 
+fn run_export(matches: &ArgMatches) -> Result<()> {
+    let mail_config = read_config()?;
+    let merged_wrs = fetch_merged_wrs(&mail_config)?;
+    let records = wr_records(&merged_wrs, pseudonymizer(matches).as_ref());
+    let format = *matches.get_one::<records::Format>("format").unwrap();
+    let output = matches.get_one::<String>("output").map(|s| s.as_str());
+    records::write_records(&records, format, output)
+}
+
+fn run_tui(matches: &ArgMatches) -> Result<()> {
+    let mut stats = stats::Stats::read_from_file(matches.get_one::<String>("stats").unwrap())?;
+    if let Some(pseudonymizer) = pseudonymizer(matches) {
+        pseudonymizer.anonymize(&mut stats);
+    }
+    tui::run(stats)
+}
+
+async fn run_db(matches: &ArgMatches) -> Result<()> {
+    let mut database = db::Database::open(matches.get_one::<String>("db").unwrap())?;
     match matches.subcommand() {
-        Some(("mailboxes", _)) => mail::list_mailboxes(&mail_config),
-        Some(("fetch-inbox", _)) => mail::fetch_inbox(&mail_config),
-        _ => {
+        Some(("sync", _)) => {
+            let mail_config = read_config()?;
             let wrs = mail::fetch_wrs(&mail_config)?;
             let replies = mail::fetch_replies(&mail_config)?;
             let merged_wrs = wr::merge_wrs(&wrs, &replies);
-            let stats = stats::Stats::from_wrs(&merged_wrs, mail_config.query.year);
-            stats.write_to_file("shared/stats.json")?;
-            let localhost = "127.0.0.1:8080";
-            let url = format!("http://{}/", localhost);
-            server::open_browser(&url);
-            server::run_server(localhost).await?;
+            database.store_wrs(&merged_wrs, mail_config.query.year)
+        }
+        Some(("stats", sub_matches)) => {
+            let mut mail_config = load_config()?;
+            if let Some(year) = sub_matches.get_one::<u32>("year") {
+                mail_config.query.year = *year;
+            }
+            let year = mail_config.query.year;
+            if !database.has_year(year)? {
+                return Err(WrError::QueryError(format!(
+                    "No WRs of {} in the database, run `db sync` first",
+                    year
+                )));
+            }
+            let mut merged_wrs = database.load_wrs(year)?;
+            merged_wrs.apply_aliases(&mail_config.aliases);
+            merged_wrs.tag_projects(&mail_config.projects);
+            let stats = stats::Stats::from_wrs(&merged_wrs, &mail_config);
+            serve_stats(stats, &merged_wrs, matches).await
+        }
+        _ => {
+            for (year, num_wrs) in database.years()? {
+                println!("{}: {} WRs", year, num_wrs);
+            }
             Ok(())
         }
+    }
+}
+
+async fn run_serve(matches: &ArgMatches) -> Result<()> {
+    let path = Path::new(matches.get_one::<String>("stats").unwrap());
+    let mut year_stats = server::find_stats(path)?;
+    if year_stats.is_empty() {
+        return Err(WrError::ConfigError(format!(
+            "No stats files found in {}",
+            path.display()
+        )));
+    }
+
+    // The stats and the WRs share the salt for the pseudonyms to match
+    let pseudonymizer = pseudonymizer(matches);
+    if let Some(pseudonymizer) = pseudonymizer.as_ref() {
+        year_stats
+            .values_mut()
+            .for_each(|stats| pseudonymizer.anonymize(stats));
+    }
+
+    // The individual WRs are only known if they were synced into a database
+    let mut records_by_year = BTreeMap::new();
+    if let Some(db_path) = matches.get_one::<String>("db") {
+        let database = db::Database::open(db_path)?;
+        let aliases = load_config()
+            .map(|config| config.aliases)
+            .unwrap_or_default();
+        for year in year_stats.keys() {
+            if database.has_year(*year)? {
+                let mut wrs = database.load_wrs(*year)?;
+                wrs.apply_aliases(&aliases);
+                records_by_year.insert(*year, wr_records(&wrs, pseudonymizer.as_ref()));
+            }
+        }
+    }
+    let data = server::ServerData {
+        stats: year_stats,
+        wr_records: records_by_year,
+        team: None,
+    };
+    serve("", data, assets(matches)).await
+}
+
+async fn run_team(matches: &ArgMatches) -> Result<()> {
+    let mut team = matches
+        .get_many::<String>("stats")
+        .unwrap_or_default()
+        .map(|file| stats::Stats::read_from_file(file))
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(pseudonymizer) = pseudonymizer(matches) {
+        team.iter_mut()
+            .for_each(|stats| pseudonymizer.anonymize(stats));
+    }
+    let team_stats = team::TeamStats::from_stats(&team);
+    team_stats.write_to_file("shared/team.json")?;
+    let data = server::ServerData {
+        team: Some(team_stats),
+        ..Default::default()
+    };
+    serve("team.html", data, assets(matches)).await
+}
+
+#[actix_web::main]
+async fn main() -> Result<()> {
+    env::set_var("RUST_LOG", "actix_server=warn,info");
+    pretty_env_logger::init();
+
+    let matches = cli().get_matches();
+
+    match matches.subcommand() {
+        Some(("team", sub_matches)) => run_team(sub_matches).await,
+        Some(("card", sub_matches)) => run_card(sub_matches),
+        Some(("report", sub_matches)) => run_report(sub_matches),
+        Some(("tui", sub_matches)) => run_tui(sub_matches),
+        Some(("serve", sub_matches)) => run_serve(sub_matches).await,
+        Some(("schema", sub_matches)) => match sub_matches.get_one::<String>("output") {
+            Some(path) => stats::write_json_file(&stats::json_schema(), path),
+            None => {
+                println!("{}", serde_json::to_string_pretty(&stats::json_schema())?);
+                Ok(())
+            }
+        },
+        Some(("export-html", sub_matches)) => run_export_html(sub_matches),
+        Some(("export", sub_matches)) => run_export(sub_matches),
+        Some(("db", sub_matches)) => run_db(sub_matches).await,
+        Some(("mailboxes", _)) => mail::list_mailboxes(&read_config()?),
+        Some(("fetch-inbox", _)) => mail::fetch_inbox(&read_config()?),
+        _ => {
+            let mail_config = read_config()?;
+            let merged_wrs = fetch_merged_wrs(&mail_config)?;
+            let stats = stats::Stats::from_wrs(&merged_wrs, &mail_config);
+            serve_stats(stats, &merged_wrs, &matches).await
+        }
     }?;
 
     Ok(())
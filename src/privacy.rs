@@ -0,0 +1,90 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+
+use log::warn;
+use sha2::{Digest, Sha256};
+
+use crate::stats::Stats;
+
+const ADJECTIVES: &[&str] = &[
+    "brave", "calm", "clever", "curious", "eager", "fancy", "gentle", "happy", "jolly", "kind",
+    "lucky", "mighty", "nimble", "proud", "quiet", "swift",
+];
+
+const ANIMALS: &[&str] = &[
+    "badger", "beaver", "falcon", "ferret", "gecko", "heron", "koala", "lemur", "lynx", "marmot",
+    "otter", "panda", "puffin", "raven", "walrus", "yak",
+];
+
+pub struct Pseudonymizer {
+    // The secret that is hashed together with each name
+    salt: String,
+}
+
+impl Pseudonymizer {
+    pub fn new(salt: Option<&str>) -> Self {
+        let salt = match salt {
+            Some(salt) => salt.to_string(),
+            None => {
+                warn!("No salt given, the pseudonyms will change with every run");
+                let random = RandomState::new().build_hasher().finish();
+                format!("{:016x}", random)
+            }
+        };
+        Pseudonymizer { salt }
+    }
+
+    // A stable, human-readable pseudonym such as "brave-otter-3f2a"
+    pub fn pseudonym(&self, name: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(name.trim().to_lowercase().as_bytes());
+        let hash = hasher.finalize();
+        format!(
+            "{}-{}-{:02x}{:02x}",
+            ADJECTIVES[hash[0] as usize % ADJECTIVES.len()],
+            ANIMALS[hash[1] as usize % ANIMALS.len()],
+            hash[2],
+            hash[3]
+        )
+    }
+
+    fn pseudonymize_keys<V>(&self, map: HashMap<String, V>) -> HashMap<String, V> {
+        map.into_iter()
+            .map(|(name, value)| (self.pseudonym(&name), value))
+            .collect()
+    }
+
+    pub fn anonymize(&self, stats: &mut Stats) {
+        // Replace all names and addresses with pseudonyms
+        stats.author = self.pseudonym(&stats.author);
+        stats.cc_histogram = self.pseudonymize_keys(std::mem::take(&mut stats.cc_histogram));
+        stats.month_cc_histogram =
+            self.pseudonymize_keys(std::mem::take(&mut stats.month_cc_histogram));
+        stats.cc_names.clear();
+        for change in stats.new_ccs.iter_mut().chain(stats.dropped_ccs.iter_mut()) {
+            change.email = self.pseudonym(&change.email);
+            change.name = None;
+        }
+
+        // Drop all text that was taken from the subjects and bodies of the WRs
+        let wrs = stats.word_counts.iter_mut();
+        let wrs = wrs.chain(stats.longest_wr.iter_mut());
+        for wr in wrs.chain(stats.shortest_wr.iter_mut()) {
+            wr.subject.clear();
+        }
+        for mood in stats
+            .most_upbeat_wr
+            .iter_mut()
+            .chain(stats.most_gloomy_wr.iter_mut())
+        {
+            mood.subject.clear();
+        }
+        stats.longest_carry_over = None;
+        stats.carry_overs.clear();
+        stats.top_words.clear();
+        stats.top_topics.clear();
+        stats.month_keywords.clear();
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use log::info;
+use serde::Serialize;
+
+use crate::error::{Result, WrError};
+use crate::privacy::Pseudonymizer;
+use crate::wr::{WRs, WR};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Csv,
+    #[value(name = "jsonl")]
+    JsonLines,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WrRecord {
+    // The date and time the WR was sent (RFC 3339)
+    pub date: String,
+    // The subject of the WR
+    pub subject: String,
+    // The number of words in the WR
+    pub num_words: usize,
+    // The number of days the WR was sent after Friday, 0 for a WR sent on Friday
+    pub wr_delay: i64,
+    // Whether the WR got a reply
+    pub replied: bool,
+    // The number of days until the reply, if any
+    pub reply_delay: Option<i64>,
+    // The normalized addresses of the people in CC
+    pub cc: Vec<String>,
+    // The text of the WR, which is only shown by the API of the web server
+    #[serde(skip_serializing)]
+    pub body: Option<String>,
+}
+
+impl WrRecord {
+    pub fn from_wr(wr: &WR) -> Self {
+        WrRecord {
+            date: wr.sent.env.date.to_rfc3339(),
+            subject: wr.sent.env.subject.clone(),
+            num_words: wr.num_words(),
+            wr_delay: wr.wr_delay(),
+            replied: wr.reply.is_some(),
+            reply_delay: wr.reply_delay(),
+            cc: wr
+                .sent
+                .env
+                .cc
+                .iter()
+                .flatten()
+                .filter_map(|address| address.normalized_email())
+                .collect(),
+            body: wr.sent.body.clone(),
+        }
+    }
+
+    pub fn anonymize(&mut self, pseudonymizer: &Pseudonymizer) {
+        self.subject.clear();
+        self.body = None;
+        for address in self.cc.iter_mut() {
+            *address = pseudonymizer.pseudonym(address);
+        }
+    }
+}
+
+// One record per WR in chronological order
+pub fn records(wrs: &WRs) -> Vec<WrRecord> {
+    let mut sorted: Vec<&WR> = wrs.wrs.iter().collect();
+    sorted.sort_by_key(|wr| wr.sent.env.date);
+    sorted.into_iter().map(WrRecord::from_wr).collect()
+}
+
+fn write_csv<W: Write>(records: &[WrRecord], writer: W) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer
+        .write_record([
+            "date",
+            "subject",
+            "num_words",
+            "wr_delay",
+            "replied",
+            "reply_delay",
+            "cc",
+        ])
+        .map_err(|e| WrError::SerializationError(e.to_string()))?;
+    for record in records {
+        // Spreadsheets cannot handle lists, so the CC addresses share one column
+        writer
+            .write_record([
+                record.date.clone(),
+                record.subject.clone(),
+                record.num_words.to_string(),
+                record.wr_delay.to_string(),
+                record.replied.to_string(),
+                record.reply_delay.map_or(String::new(), |d| d.to_string()),
+                record.cc.join(";"),
+            ])
+            .map_err(|e| WrError::SerializationError(e.to_string()))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_json_lines<W: Write>(records: &[WrRecord], mut writer: W) -> Result<()> {
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// Write the records to stdout or to a file
+pub fn write_records(records: &[WrRecord], format: Format, output: Option<&str>) -> Result<()> {
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    match format {
+        Format::Csv => write_csv(records, writer)?,
+        Format::JsonLines => write_json_lines(records, writer)?,
+    }
+    if let Some(path) = output {
+        info!("Wrote {} WRs to {}", records.len(), path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wr::tests::wr_sent_at;
+
+    #[test]
+    fn friday_wrs_are_exported_on_time() {
+        let mut wrs = WRs::new();
+        wrs.wrs.push(wr_sent_at("2023-03-04T10:00:00+01:00"));
+        wrs.wrs.push(wr_sent_at("2023-03-03T17:00:00+01:00"));
+
+        let mut csv = Vec::new();
+        write_csv(&records(&wrs), &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[1], "2023-03-03T17:00:00+01:00,WR,0,0,false,,");
+        assert_eq!(lines[2], "2023-03-04T10:00:00+01:00,WR,0,1,false,,");
+    }
+}
@@ -0,0 +1,189 @@
+use std::fs;
+
+use clap::ValueEnum;
+use log::info;
+
+use crate::error::Result;
+use crate::stats::{self, Stats};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    #[value(alias = "md")]
+    Markdown,
+    #[value(alias = "txt")]
+    Text,
+}
+
+struct Section {
+    title: String,
+    // The label and value of each line
+    rows: Vec<(String, String)>,
+}
+
+impl Section {
+    fn new(title: &str) -> Self {
+        Section {
+            title: title.to_string(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn row(&mut self, label: &str, value: impl ToString) {
+        self.rows.push((label.to_string(), value.to_string()));
+    }
+}
+
+fn days(value: f64) -> String {
+    format!("{:.1} days", value)
+}
+
+fn sections(stats: &Stats) -> Vec<Section> {
+    let mut sections = Vec::new();
+
+    let mut overview = Section::new("Overview");
+    overview.row("WRs written", stats.num_wrs);
+    overview.row("Longest streak", format!("{} weeks", stats.longest_streak));
+    overview.row(
+        "WRs with a reply",
+        format!(
+            "{} ({:.0}%)",
+            stats.num_replied_wrs,
+            stats.ratio_replied_wrs * 100.0
+        ),
+    );
+    overview.row("Words written", stats.num_words);
+    overview.row(
+        "Words per WR",
+        format!(
+            "{:.0} on average, {:.0} median",
+            stats.words_per_wr.mean, stats.words_per_wr.median
+        ),
+    );
+    if let Some(longest) = &stats.longest_wr {
+        overview.row(
+            "Longest WR",
+            format!("{} words on {}", longest.num_words, longest.date),
+        );
+    }
+    sections.push(overview);
+
+    let mut timing = Section::new("Timing");
+    if let Some(weekday) = stats.favourite_weekday() {
+        timing.row("Favourite weekday", stats::weekday_name(weekday));
+    }
+    if let Some((hour, _)) = stats
+        .hour_wr_histogram
+        .iter()
+        .filter(|(_, count)| **count > 0)
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+    {
+        timing.row("Favourite hour", format!("{:02}:00", hour));
+    }
+    timing.row("Delay after Friday", days(stats.wr_delay.mean));
+    timing.row("Time until a reply", days(stats.reply_delay.mean));
+    sections.push(timing);
+
+    let mut people = Section::new("Most CC'd");
+    for (name, count) in stats.top_ccs(5) {
+        people.row(&name, format!("{}x", count));
+    }
+    sections.push(people);
+
+    let mut projects: Vec<(&String, &u32)> = stats.project_histogram.iter().collect();
+    projects.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let mut project_section = Section::new("Projects");
+    for (project, count) in projects.into_iter().take(5) {
+        project_section.row(project, format!("{} WRs", count));
+    }
+    sections.push(project_section);
+
+    let mut style = Section::new("Writing style");
+    style.row("Reading ease", format!("{:.0}", stats.reading_ease.mean));
+    style.row(
+        "Words per sentence",
+        format!("{:.1}", stats.sentence_length.mean),
+    );
+    style.row(
+        "Bullet points",
+        format!("{:.0}% of lines", stats.bullet_ratio.mean * 100.0),
+    );
+    style.row("Emojis", stats.num_emojis);
+    style.row("Exclamation marks", stats.num_exclamation_marks);
+    style.row("Links", stats.num_links);
+    style.row("Mood", format!("{:+.1}", stats.wr_mood.mean));
+    sections.push(style);
+
+    let mut words = Section::new("Top words");
+    for word in stats.top_words.iter().take(10) {
+        words.row(&word.term, format!("{}x", word.count));
+    }
+    sections.push(words);
+
+    let mut achievements = Section::new("Achievements");
+    for achievement in stats.achievements.iter() {
+        achievements.row(&achievement.name, &achievement.description);
+    }
+    sections.push(achievements);
+
+    // Anonymized stats or years without CCs have nothing to show in some sections
+    sections.retain(|section| !section.rows.is_empty());
+    sections
+}
+
+fn render_markdown(stats: &Stats, sections: &[Section]) -> String {
+    let mut report = format!("# WRapped {}\n", stats.year);
+    for section in sections {
+        report.push_str(&format!("\n## {}\n\n", section.title));
+        report.push_str("| | |\n|---|---|\n");
+        for (label, value) in section.rows.iter() {
+            let escape = |text: &str| text.replace('|', "\\|");
+            report.push_str(&format!("| {} | {} |\n", escape(label), escape(value)));
+        }
+    }
+    report
+}
+
+fn render_text(stats: &Stats, sections: &[Section]) -> String {
+    let title = format!("WRapped {}", stats.year);
+    let mut report = format!("{}\n{}\n", title, "=".repeat(title.len()));
+    for section in sections {
+        report.push_str(&format!(
+            "\n{}\n{}\n",
+            section.title,
+            "-".repeat(section.title.chars().count())
+        ));
+        // Align the values of a section in one column
+        let width = section
+            .rows
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (label, value) in section.rows.iter() {
+            let padding = width - label.chars().count();
+            report.push_str(&format!("{}:{} {}\n", label, " ".repeat(padding), value));
+        }
+    }
+    report
+}
+
+pub fn render(stats: &Stats, format: Format) -> String {
+    let sections = sections(stats);
+    match format {
+        Format::Markdown => render_markdown(stats, &sections),
+        Format::Text => render_text(stats, &sections),
+    }
+}
+
+// Print the report to stdout or write it to a file
+pub fn write_report(stats: &Stats, format: Format, output: Option<&str>) -> Result<()> {
+    let report = render(stats, format);
+    match output {
+        Some(path) => {
+            fs::write(path, report)?;
+            info!("Wrote the report to {}", path);
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
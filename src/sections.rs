@@ -0,0 +1,206 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub struct Section {
+    // The name of the section as configured
+    pub name: String,
+    // The items (bullet points or paragraphs) of the section
+    pub items: Vec<String>,
+}
+
+pub struct SectionParser {
+    // The section names with their lowercase headings, longest heading first
+    headings: Vec<(String, String)>,
+}
+
+impl SectionParser {
+    pub fn new(sections: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut headings: Vec<(String, String)> = sections
+            .iter()
+            .flat_map(|(name, headings)| {
+                headings
+                    .iter()
+                    .map(move |heading| (name.clone(), heading.to_lowercase()))
+            })
+            .collect();
+        // Match "Next week" before "Next" if both are configured
+        headings.sort_by_key(|(_, heading)| Reverse(heading.len()));
+        SectionParser { headings }
+    }
+
+    fn match_heading(&self, line: &str) -> Option<&str> {
+        // Headings are short and may be decorated with Markdown syntax
+        if line.chars().count() > 40 {
+            return None;
+        }
+        let normalized = line
+            .trim_start_matches(|c: char| c == '#' || c == '*' || c == '_' || c.is_whitespace())
+            .trim_end_matches(|c: char| c == ':' || c == '*' || c == '_' || c.is_whitespace())
+            .to_lowercase();
+        self.headings
+            .iter()
+            .find(|(_, heading)| {
+                normalized
+                    .strip_prefix(heading.as_str())
+                    .is_some_and(|rest| {
+                        !rest.starts_with(char::is_alphanumeric) && is_heading_suffix(rest)
+                    })
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub fn parse(&self, body: &str) -> Vec<Section> {
+        let mut sections: Vec<Section> = Vec::new();
+        // Whether the previous line belongs to an item that may continue
+        let mut in_item = false;
+
+        for line in body.lines() {
+            // Stop at the signature and skip quoted text of previous mails
+            if line == "-- " {
+                break;
+            }
+            let trimmed = line.trim();
+            if trimmed.starts_with('>') {
+                continue;
+            }
+            if trimmed.is_empty() {
+                in_item = false;
+                continue;
+            }
+
+            // Bullets are always items, even if they start with a heading such as "* Issues"
+            let bullet = strip_bullet(trimmed);
+            if let Some(name) = self.match_heading(trimmed).filter(|_| bullet.is_none()) {
+                sections.push(Section {
+                    name: name.to_string(),
+                    items: Vec::new(),
+                });
+                in_item = false;
+                continue;
+            }
+
+            // Text before the first heading does not belong to any section
+            let section = match sections.last_mut() {
+                Some(section) => section,
+                None => continue,
+            };
+
+            match bullet {
+                Some(item) => section.items.push(item.to_string()),
+                None => match section.items.last_mut() {
+                    Some(last) if in_item => {
+                        last.push(' ');
+                        last.push_str(trimmed);
+                    }
+                    _ => section.items.push(trimmed.to_string()),
+                },
+            }
+            in_item = true;
+        }
+
+        sections
+    }
+}
+
+// Only a short note may follow a heading, such as "Done (KW 12)" or "Next week - 12.03.",
+// so that prose such as "Issues with the build were fixed" stays an item
+fn is_heading_suffix(rest: &str) -> bool {
+    let rest =
+        rest.trim_start_matches(|c: char| c == '-' || c == '–' || c == ':' || c.is_whitespace());
+    rest.is_empty()
+        || (rest.starts_with('(') && rest.ends_with(')'))
+        || rest
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '/' || c == '-' || c.is_whitespace())
+}
+
+pub fn count_items(sections: &[Section], name: &str) -> usize {
+    sections
+        .iter()
+        .filter(|section| section.name == name)
+        .map(|section| section.items.len())
+        .sum()
+}
+
+pub fn strip_bullet(line: &str) -> Option<&str> {
+    for bullet in ["- ", "* ", "+ ", "• ", "– "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some(item.trim());
+        }
+    }
+    // Numbered lists such as "1." or "2)"
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        let rest = &line[digits..];
+        if let Some(item) = rest.strip_prefix(". ").or(rest.strip_prefix(") ")) {
+            return Some(item.trim());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> SectionParser {
+        let sections = BTreeMap::from([
+            ("done".to_string(), vec!["Done".to_string()]),
+            ("next".to_string(), vec!["Next".to_string()]),
+            ("blockers".to_string(), vec!["Issues".to_string()]),
+        ]);
+        SectionParser::new(&sections)
+    }
+
+    fn items(sections: &[Section], name: &str) -> Vec<String> {
+        sections
+            .iter()
+            .filter(|section| section.name == name)
+            .flat_map(|section| section.items.clone())
+            .collect()
+    }
+
+    #[test]
+    fn headings_are_recognized() {
+        let sections = parser().parse("## Done:\n- a thing\n**Issues**\n- a problem");
+        assert_eq!(items(&sections, "done"), ["a thing"]);
+        assert_eq!(items(&sections, "blockers"), ["a problem"]);
+    }
+
+    #[test]
+    fn headings_may_have_a_short_suffix() {
+        let sections =
+            parser().parse("Done (KW 12)\n- a thing\nNext - 24.03.2023\n- another thing");
+        assert_eq!(items(&sections, "done"), ["a thing"]);
+        assert_eq!(items(&sections, "next"), ["another thing"]);
+    }
+
+    #[test]
+    fn prose_starting_with_a_heading_is_an_item() {
+        let sections = parser().parse("Done\nIssues with the build were fixed\n\nAll good");
+        assert_eq!(
+            items(&sections, "done"),
+            ["Issues with the build were fixed", "All good"]
+        );
+        assert!(items(&sections, "blockers").is_empty());
+    }
+
+    #[test]
+    fn bullets_starting_with_a_heading_are_items() {
+        let cases = [
+            ("* Issues with the build", "Issues with the build"),
+            ("* Next steps", "Next steps"),
+            ("- Next steps are planned", "Next steps are planned"),
+            ("- Done", "Done"),
+            ("1. Issues to file", "Issues to file"),
+            ("2) Done criteria", "Done criteria"),
+        ];
+        for (line, item) in cases {
+            let sections = parser().parse(&format!("Done\n{}", line));
+            assert_eq!(items(&sections, "done"), [item], "{}", line);
+            assert!(items(&sections, "next").is_empty(), "{}", line);
+            assert!(items(&sections, "blockers").is_empty(), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use crate::text;
+
+// A small valence lexicon in the spirit of AFINN, scores range from -3 to 3
+const LEXICON: &[(&str, i32)] = &[
+    // English
+    ("amazing", 3),
+    ("awesome", 3),
+    ("best", 3),
+    ("excellent", 3),
+    ("fantastic", 3),
+    ("great", 3),
+    ("love", 3),
+    ("loved", 3),
+    ("perfect", 3),
+    ("thrilled", 3),
+    ("wonderful", 3),
+    ("accomplished", 2),
+    ("achieved", 2),
+    ("better", 2),
+    ("easy", 2),
+    ("enjoyed", 2),
+    ("excited", 2),
+    ("finished", 2),
+    ("glad", 2),
+    ("good", 2),
+    ("happy", 2),
+    ("improved", 2),
+    ("nice", 2),
+    ("progress", 2),
+    ("solved", 2),
+    ("success", 2),
+    ("successful", 2),
+    ("successfully", 2),
+    ("working", 2),
+    ("works", 2),
+    ("accepted", 1),
+    ("completed", 1),
+    ("fine", 1),
+    ("fixed", 1),
+    ("helpful", 1),
+    ("interesting", 1),
+    ("merged", 1),
+    ("ok", 1),
+    ("okay", 1),
+    ("promising", 1),
+    ("resolved", 1),
+    ("thanks", 1),
+    ("delay", -1),
+    ("delayed", -1),
+    ("difficult", -1),
+    ("hard", -1),
+    ("issue", -1),
+    ("issues", -1),
+    ("missing", -1),
+    ("slow", -1),
+    ("tricky", -1),
+    ("unclear", -1),
+    ("unfortunately", -1),
+    ("wrong", -1),
+    ("annoying", -2),
+    ("bad", -2),
+    ("blocked", -2),
+    ("bug", -2),
+    ("bugs", -2),
+    ("crash", -2),
+    ("crashed", -2),
+    ("error", -2),
+    ("errors", -2),
+    ("fail", -2),
+    ("failed", -2),
+    ("failing", -2),
+    ("frustrating", -2),
+    ("problem", -2),
+    ("problems", -2),
+    ("rejected", -2),
+    ("sick", -2),
+    ("stuck", -2),
+    ("tired", -2),
+    ("unhappy", -2),
+    ("worried", -2),
+    ("worse", -2),
+    ("broken", -3),
+    ("disaster", -3),
+    ("hate", -3),
+    ("horrible", -3),
+    ("terrible", -3),
+    ("worst", -3),
+    // German
+    ("ausgezeichnet", 3),
+    ("beste", 3),
+    ("hervorragend", 3),
+    ("perfekt", 3),
+    ("super", 3),
+    ("toll", 3),
+    ("besser", 2),
+    ("erfolgreich", 2),
+    ("erledigt", 2),
+    ("fertig", 2),
+    ("freue", 2),
+    ("gelöst", 2),
+    ("gut", 2),
+    ("gute", 2),
+    ("guten", 2),
+    ("funktioniert", 2),
+    ("fortschritt", 2),
+    ("behoben", 1),
+    ("geschafft", 1),
+    ("interessant", 1),
+    ("danke", 1),
+    ("langsam", -1),
+    ("leider", -1),
+    ("schwierig", -1),
+    ("unklar", -1),
+    ("verzögerung", -1),
+    ("blockiert", -2),
+    ("fehler", -2),
+    ("krank", -2),
+    ("müde", -2),
+    ("problem", -2),
+    ("probleme", -2),
+    ("abgelehnt", -2),
+    ("schlecht", -2),
+    ("schlechter", -2),
+    ("schlimm", -2),
+    ("kaputt", -3),
+    ("katastrophe", -3),
+    ("schrecklich", -3),
+];
+
+// Words that flip the valence of the word that follows them
+const NEGATIONS: &[&str] = &[
+    "no", "not", "never", "don't", "doesn't", "didn't", "isn't", "wasn't", "nicht", "kein",
+    "keine", "nie",
+];
+
+pub struct SentimentAnalyzer {
+    // The valence of each word in the lexicon
+    lexicon: HashMap<&'static str, i32>,
+}
+
+impl Default for SentimentAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SentimentAnalyzer {
+    pub fn new() -> Self {
+        SentimentAnalyzer {
+            lexicon: LEXICON.iter().cloned().collect(),
+        }
+    }
+
+    // The sum of the valences of all words per 100 words
+    pub fn score(&self, text: &str) -> f64 {
+        let mut sum = 0;
+        let mut num_words = 0;
+        let mut negated = false;
+
+        for word in text::words(text) {
+            num_words += 1;
+            if let Some(valence) = self.lexicon.get(word.as_str()) {
+                sum += if negated { -valence } else { *valence };
+            }
+            negated = NEGATIONS.contains(&word.as_str());
+        }
+
+        if num_words == 0 {
+            return 0.0;
+        }
+        100.0 * sum as f64 / num_words as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_words_have_a_valence() {
+        let analyzer = SentimentAnalyzer::new();
+        for text in ["bad", "worse", "worst", "I hate this bug"] {
+            assert!(analyzer.score(text) < 0.0, "{}", text);
+        }
+        for text in ["better", "I love this", "not bad"] {
+            assert!(analyzer.score(text) > 0.0, "{}", text);
+        }
+    }
+
+    #[test]
+    fn negations_flip_the_valence() {
+        let analyzer = SentimentAnalyzer::new();
+        assert_eq!(analyzer.score("good week"), 100.0);
+        assert_eq!(analyzer.score("not good"), -100.0);
+    }
+
+    #[test]
+    fn empty_text_is_neutral() {
+        assert_eq!(SentimentAnalyzer::new().score(""), 0.0);
+    }
+}
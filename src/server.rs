@@ -1,10 +1,91 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::Duration;
 
-use actix_files as fs;
-use actix_web::{App, HttpServer};
+use actix_web::{error, web, App, HttpRequest, HttpResponse, HttpServer};
+use serde::{Deserialize, Serialize};
 
-use log::{error, info};
+use log::{debug, error, info};
+
+use crate::assets::Assets;
+use crate::error::Result;
+use crate::records::WrRecord;
+use crate::stats::Stats;
+use crate::team::TeamStats;
+
+// The stats of each year that can be served
+pub type YearStats = BTreeMap<u32, Stats>;
+
+const DEFAULT_PER_PAGE: usize = 20;
+const MAX_PER_PAGE: usize = 100;
+
+#[derive(Debug, Default)]
+pub struct ServerData {
+    // The stats of each year
+    pub stats: YearStats,
+    // The WRs of each year in chronological order, if they are known
+    pub wr_records: BTreeMap<u32, Vec<WrRecord>>,
+    // The team stats in team mode
+    pub team: Option<TeamStats>,
+}
+
+impl ServerData {
+    // The requested year, or the latest year if none was requested
+    fn year(&self, year: Option<u32>) -> Option<u32> {
+        year.or_else(|| {
+            let latest_stats = self.stats.keys().last();
+            latest_stats.or(self.wr_records.keys().last()).copied()
+        })
+    }
+
+    fn wr_records(&self, year: Option<u32>) -> actix_web::Result<(u32, &[WrRecord])> {
+        let year = self.year(year);
+        year.and_then(|year| self.wr_records.get(&year).map(|wrs| (year, wrs.as_slice())))
+            .ok_or_else(|| error::ErrorNotFound("No WRs for this year"))
+    }
+}
+
+#[derive(Deserialize)]
+struct YearQuery {
+    year: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    year: Option<u32>,
+    // The page to return, starting at 1
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct WrSummary<'a> {
+    // The index of the WR in the year
+    id: usize,
+    #[serde(flatten)]
+    record: &'a WrRecord,
+}
+
+#[derive(Serialize)]
+struct WrPage<'a> {
+    year: u32,
+    page: usize,
+    per_page: usize,
+    // The number of WRs in the year
+    total: usize,
+    wrs: Vec<WrSummary<'a>>,
+}
+
+#[derive(Serialize)]
+struct WrDetail<'a> {
+    year: u32,
+    id: usize,
+    #[serde(flatten)]
+    record: &'a WrRecord,
+    body: Option<&'a str>,
+}
 
 pub fn open_browser(server: &str) {
     thread::sleep(Duration::from_secs(1));
@@ -13,13 +94,154 @@ pub fn open_browser(server: &str) {
     }
 }
 
-pub async fn run_server(server: &str) -> std::io::Result<()> {
+// Read the stats of a single file or of all stats files in a directory
+pub fn find_stats(path: &Path) -> Result<YearStats> {
+    let paths: Vec<PathBuf> = if path.is_dir() {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry_path = entry?.path();
+            if entry_path.extension().is_some_and(|ext| ext == "json") {
+                paths.push(entry_path);
+            }
+        }
+        paths.sort();
+        paths
+    } else {
+        // A single file has to be a valid stats file
+        let stats = Stats::read_from_file(&path.to_string_lossy())?;
+        info!(
+            "Serving the stats of {} from {}",
+            stats.year,
+            path.display()
+        );
+        return Ok(BTreeMap::from([(stats.year, stats)]));
+    };
+
+    let mut files = BTreeMap::new();
+    let mut year_stats = YearStats::new();
+    for path in paths {
+        // Other JSON files such as the team stats may share the directory
+        match Stats::read_from_file(&path.to_string_lossy()) {
+            Ok(stats) => {
+                if let Some(previous) = files.insert(stats.year, path.clone()) {
+                    info!(
+                        "Serving {} instead of {} for {}",
+                        path.display(),
+                        previous.display(),
+                        stats.year
+                    );
+                }
+                year_stats.insert(stats.year, stats);
+            }
+            Err(e) => debug!("Skipping {}: {}", path.display(), e),
+        }
+    }
+    for (year, path) in files.iter() {
+        info!("Serving the stats of {} from {}", year, path.display());
+    }
+    Ok(year_stats)
+}
+
+// The stats of the requested year, or of the latest year
+async fn api_stats(
+    data: web::Data<ServerData>,
+    query: web::Query<YearQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let stats = data
+        .year(query.year)
+        .and_then(|year| data.stats.get(&year))
+        .ok_or_else(|| error::ErrorNotFound("No stats for this year"))?;
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+async fn api_years(data: web::Data<ServerData>) -> HttpResponse {
+    HttpResponse::Ok().json(data.stats.keys().collect::<Vec<_>>())
+}
+
+async fn api_team(data: web::Data<ServerData>) -> actix_web::Result<HttpResponse> {
+    let team = data
+        .team
+        .as_ref()
+        .ok_or_else(|| error::ErrorNotFound("No team stats"))?;
+    Ok(HttpResponse::Ok().json(team))
+}
+
+async fn api_wrs(
+    data: web::Data<ServerData>,
+    query: web::Query<PageQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let (year, wrs) = data.wr_records(query.year)?;
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query
+        .per_page
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+    let page_wrs = wrs
+        .iter()
+        .enumerate()
+        // Pages far beyond the last WR are empty instead of overflowing
+        .skip((page - 1).saturating_mul(per_page))
+        .take(per_page)
+        .map(|(id, record)| WrSummary { id, record })
+        .collect();
+    Ok(HttpResponse::Ok().json(WrPage {
+        year,
+        page,
+        per_page,
+        total: wrs.len(),
+        wrs: page_wrs,
+    }))
+}
+
+async fn api_wr(
+    data: web::Data<ServerData>,
+    id: web::Path<usize>,
+    query: web::Query<YearQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let (year, wrs) = data.wr_records(query.year)?;
+    let id = id.into_inner();
+    let record = wrs
+        .get(id)
+        .ok_or_else(|| error::ErrorNotFound("No WR with this id"))?;
+    Ok(HttpResponse::Ok().json(WrDetail {
+        year,
+        id,
+        record,
+        body: record.body.as_deref(),
+    }))
+}
+
+// The frontend, with the index page for directories
+async fn asset(req: HttpRequest, assets: web::Data<Assets>) -> HttpResponse {
+    let mut path = req.path().trim_start_matches('/').to_string();
+    if path.is_empty() || path.ends_with('/') {
+        path.push_str("index.html");
+    }
+    match assets.get(&path) {
+        Some((data, mimetype)) => HttpResponse::Ok()
+            .content_type(mimetype)
+            .body(data.into_owned()),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+pub async fn run_server(server: &str, data: ServerData, assets: Assets) -> std::io::Result<()> {
     info!("Starting the web server at {}", server);
+    let data = web::Data::new(data);
+    let assets = web::Data::new(assets);
     HttpServer::new(move || {
         App::new()
-            // Your routes and server configuration here
-            .service(fs::Files::new("/stats", "./shared"))
-            .service(fs::Files::new("/", "./web/").index_file("index.html"))
+            .app_data(data.clone())
+            .app_data(assets.clone())
+            .service(
+                web::scope("/api")
+                    .route("/stats", web::get().to(api_stats))
+                    .route("/years", web::get().to(api_years))
+                    .route("/team", web::get().to(api_team))
+                    .route("/wrs", web::get().to(api_wrs))
+                    .route("/wrs/{id}", web::get().to(api_wr)),
+            )
+            .default_service(web::get().to(asset))
     })
     .workers(1)
     .bind(server)?
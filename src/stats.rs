@@ -3,70 +3,728 @@ use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Weekday};
+
+use crate::achievements::{self, Achievement};
+use crate::carryover::{self, CarryOver};
+use crate::config::MailConfig;
 use crate::error::{Result, WrError};
-use crate::wr::WRs;
+use crate::sections::{self, SectionParser};
+use crate::sentiment::SentimentAnalyzer;
+use crate::style::WritingStyle;
+use crate::text::{self, TermCount, TermScore, Tokenizer};
+use crate::wr::{WRs, WR};
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Summary {
+    /// The number of values
+    pub count: usize,
+    /// The arithmetic mean of the values
+    pub mean: f64,
+    /// The median of the values
+    pub median: f64,
+    /// The 90th percentile of the values
+    pub p90: f64,
+    /// The smallest value
+    pub min: f64,
+    /// The largest value
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn from_values(values: &[f64]) -> Self {
+        // An empty input results in a summary of zeros instead of NaN
+        if values.is_empty() {
+            return Summary::default();
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let count = sorted.len();
+
+        Summary {
+            count,
+            mean: sorted.iter().sum::<f64>() / count as f64,
+            median: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            min: sorted[0],
+            max: sorted[count - 1],
+        }
+    }
+}
+
+// Linearly interpolated percentile of an already sorted, non-empty slice
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WordCount {
+    /// The date the WR was sent
+    pub date: String,
+    /// The subject of the WR
+    pub subject: String,
+    /// The number of words in the WR
+    pub num_words: usize,
+}
+
+impl WordCount {
+    pub fn from_wr(wr: &WR) -> Self {
+        WordCount {
+            date: wr.sent.env.date.format("%Y-%m-%d").to_string(),
+            subject: wr.sent.env.subject.clone(),
+            num_words: wr.num_words(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CcChange {
+    /// The normalized email address of the person
+    pub email: String,
+    /// The display name of the person, if known
+    pub name: Option<String>,
+    /// The date of the first (new) or last (dropped) WR the person was in CC
+    pub date: String,
+    /// The number of WRs the person was in CC
+    pub count: usize,
+}
+
+// The people that were first CC'd more than a month after the first WR (new),
+// and the people that were last CC'd more than a month before the last WR (dropped)
+fn cc_changes(wrs: &WRs, names: &HashMap<String, String>) -> (Vec<CcChange>, Vec<CcChange>) {
+    let dates: Vec<_> = wrs.wrs.iter().map(|wr| wr.sent.env.date).collect();
+    let (first_wr, last_wr) = match (dates.iter().min(), dates.iter().max()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return (Vec::new(), Vec::new()),
+    };
+    let grace = Duration::days(30);
+
+    let mut new_ccs = Vec::new();
+    let mut dropped_ccs = Vec::new();
+    for (email, cc_dates) in wrs.cc_dates() {
+        let first_cc = *cc_dates.iter().min().unwrap();
+        let last_cc = *cc_dates.iter().max().unwrap();
+        let change = |date: DateTime<FixedOffset>| CcChange {
+            email: email.clone(),
+            name: names.get(&email).cloned(),
+            date: date.format("%Y-%m-%d").to_string(),
+            count: cc_dates.len(),
+        };
+        if first_cc > first_wr + grace {
+            new_ccs.push(change(first_cc));
+        }
+        if last_cc < last_wr - grace {
+            dropped_ccs.push(change(last_cc));
+        }
+    }
+    new_ccs.sort_by(|a, b| b.count.cmp(&a.count).then(a.email.cmp(&b.email)));
+    dropped_ccs.sort_by(|a, b| b.count.cmp(&a.count).then(a.email.cmp(&b.email)));
+    (new_ccs, dropped_ccs)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Mood {
+    /// The date the WR was sent
+    pub date: String,
+    /// The subject of the WR
+    pub subject: String,
+    /// The sentiment score of the WR per 100 words
+    pub score: f64,
+}
+
+// Pearson correlation coefficient, zero if it is not defined
+fn correlation(points: &[(f64, f64)]) -> f64 {
+    if points.len() < 2 {
+        return 0.0;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let cov_xy: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let var_x: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let var_y: f64 = points.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    if var_x == 0.0 || var_y == 0.0 {
+        return 0.0;
+    }
+    cov_xy / (var_x * var_y).sqrt()
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Trend {
+    /// The change per week of the least-squares fit
+    pub slope_per_week: f64,
+    /// The relative change of the fit between the first and the last data point
+    pub relative_change: f64,
+}
+
+impl Trend {
+    pub fn from_series(series: &[(DateTime<FixedOffset>, f64)]) -> Self {
+        // Express the date of each data point in weeks since the first one
+        let first = match series.iter().map(|(date, _)| *date).min() {
+            Some(first) => first,
+            None => return Trend::default(),
+        };
+        let points: Vec<(f64, f64)> = series
+            .iter()
+            .map(|(date, value)| {
+                let secs = date.signed_duration_since(first).num_seconds();
+                (secs as f64 / (7.0 * 86400.0), *value)
+            })
+            .collect();
+
+        // A trend needs at least two distinct points in time
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let var_x: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        if var_x == 0.0 {
+            return Trend::default();
+        }
+        let cov_xy: f64 = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum();
+        let slope = cov_xy / var_x;
+        let intercept = mean_y - slope * mean_x;
 
-#[derive(Debug, Serialize)]
+        let last_x = points.iter().map(|(x, _)| *x).fold(0.0, f64::max);
+        let first_fit = intercept;
+        let last_fit = intercept + slope * last_x;
+        let relative_change = if first_fit > 0.0 {
+            (last_fit - first_fit) / first_fit
+        } else {
+            0.0
+        };
+
+        Trend {
+            slope_per_week: slope,
+            relative_change,
+        }
+    }
+}
+
+// The version of the format of the stats file, increase it on incompatible changes
+// (version 2 counts `wr_delay` from Friday instead of Saturday and leaves the
+// `month_mood` of months without WRs empty instead of 0)
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Stats {
-    // The year of the WRs
+    /// The version of the format of the stats file, 0 if it was written before versioning
+    #[serde(default)]
+    pub schema_version: u32,
+    /// The year of the WRs
     pub year: u32,
-    // The number of WRs
+    /// The address the WRs were sent from
+    pub author: String,
+    /// The number of WRs
     pub num_wrs: usize,
-    // The number of WRs that were replied to
+    /// The largest number of consecutive weeks with a WR
+    pub longest_streak: usize,
+    /// The number of WRs that were replied to
     pub num_replied_wrs: usize,
-    // The ratio of WRs that were replied to
+    /// The ratio of WRs that were replied to
     pub ratio_replied_wrs: f64,
-    // The number of words written
+    /// The number of words written
     pub num_words: usize,
-    // The average delay of the WRs
-    pub avg_wr_delay: f64,
-    // The average delay of the replied WRs
-    pub avg_reply_delay: f64,
-    // The histogram of the day of the week the WRs were sent
+    /// The number of words per WR
+    pub words_per_wr: Summary,
+    /// The number of words of each WR
+    pub word_counts: Vec<WordCount>,
+    /// The histogram of the words written per month
+    pub month_words_histogram: HashMap<u32, u32>,
+    /// The linear trend of the number of words over the year
+    pub words_trend: Trend,
+    /// The WR with the most words
+    pub longest_wr: Option<WordCount>,
+    /// The WR with the fewest words
+    pub shortest_wr: Option<WordCount>,
+    /// The delay of the WRs in days since Friday, 0 for a WR sent on Friday
+    pub wr_delay: Summary,
+    /// The delay of the replies in days since the WR was sent
+    pub reply_delay: Summary,
+    /// The number of attachments sent with the WRs
+    pub num_attachments: usize,
+    /// The number of attachments per WR
+    pub attachments_per_wr: Summary,
+    /// The total size of the attachments in megabytes
+    pub attachment_megabytes: f64,
+    /// The histogram of the MIME types of the attachments
+    pub attachment_type_histogram: HashMap<String, u32>,
+    /// The histogram of the day of the week the WRs were sent
     pub weekday_wr_histogram: HashMap<u32, u32>,
-    // The histogram of the day of the week the WRs were replied to
+    /// The histogram of the day of the week the WRs were replied to
     pub weekday_reply_histogram: HashMap<u32, u32>,
-    // The histogram of the hour of the day the WRs were sent
+    /// The histogram of the hour of the day the WRs were sent
     pub hour_wr_histogram: HashMap<u32, u32>,
-    // The histogram of the hour of the day the WRs were replied to
+    /// The histogram of the hour of the day the WRs were replied to
     pub hour_reply_histogram: HashMap<u32, u32>,
-    // The histogram of the people that were in CC of the WRs
+    /// The heatmap of the replies per day of the week (rows) and hour of the day (columns)
+    pub weekday_hour_reply_heatmap: Vec<Vec<u32>>,
+    /// The histogram of the people (by email address) that were in CC of the WRs
     pub cc_histogram: HashMap<String, u32>,
+    /// The display names of the people that were in CC of the WRs
+    pub cc_names: HashMap<String, String>,
+    /// The histogram of the WRs per month each person was in CC
+    pub month_cc_histogram: HashMap<String, HashMap<u32, u32>>,
+    /// The people that were first CC'd later in the year, most CC'd first
+    pub new_ccs: Vec<CcChange>,
+    /// The people that were no longer CC'd towards the end of the year, most CC'd first
+    pub dropped_ccs: Vec<CcChange>,
+    /// The number of WRs that mention each project
+    pub project_histogram: HashMap<String, u32>,
+    /// The histogram of the WRs per month that mention each project
+    pub month_project_histogram: HashMap<String, HashMap<u32, u32>>,
+    /// The sentiment of the WRs per 100 words
+    pub wr_mood: Summary,
+    /// The sentiment of the replies per 100 words
+    pub reply_mood: Summary,
+    /// The average sentiment of the WRs per month, null for months without WRs
+    pub month_mood: HashMap<u32, Option<f64>>,
+    /// The WR with the most positive sentiment
+    pub most_upbeat_wr: Option<Mood>,
+    /// The WR with the most negative sentiment
+    pub most_gloomy_wr: Option<Mood>,
+    /// The correlation between the sentiment of a WR and the delay of its reply
+    pub mood_reply_delay_correlation: f64,
+    /// The Flesch reading ease of the WRs
+    pub reading_ease: Summary,
+    /// The average number of words per sentence of the WRs
+    pub sentence_length: Summary,
+    /// The ratio of lines that are bullet points of the WRs
+    pub bullet_ratio: Summary,
+    /// The number of emojis used in all WRs
+    pub num_emojis: usize,
+    /// The number of exclamation marks used in all WRs
+    pub num_exclamation_marks: usize,
+    /// The number of links shared in all WRs
+    pub num_links: usize,
+    /// The number of items in each section of the WRs
+    pub section_item_counts: HashMap<String, usize>,
+    /// The histogram of the items per month of each section
+    pub month_section_histogram: HashMap<String, HashMap<u32, u32>>,
+    /// The linear trend of the number of items per WR of each section
+    pub section_trends: HashMap<String, Trend>,
+    /// The number of TODO items that were carried over to the next WR
+    pub num_carry_overs: usize,
+    /// The TODO item that lingered the longest over consecutive WRs
+    pub longest_carry_over: Option<CarryOver>,
+    /// The TODO items that lingered the longest over consecutive WRs
+    pub carry_overs: Vec<CarryOver>,
+    /// The most frequent words of the year
+    pub top_words: Vec<TermCount>,
+    /// The words with the highest TF-IDF score accumulated over all WRs
+    pub top_topics: Vec<TermScore>,
+    /// The words with the highest TF-IDF score of each month
+    pub month_keywords: HashMap<u32, Vec<TermScore>>,
+    /// The badges that were earned
+    pub achievements: Vec<Achievement>,
 }
 
 impl Stats {
-    pub fn from_wrs(wrs: &WRs, year: u32) -> Self {
-        Stats {
-            year,
+    pub fn from_wrs(wrs: &WRs, config: &MailConfig) -> Self {
+        let num_top_words = config.text.num_top_words;
+        let tokenizer = Tokenizer::new(&config.text);
+        let wr_tokens: Vec<Vec<String>> = wrs
+            .wrs
+            .iter()
+            .map(|wr| tokenizer.tokenize(wr.sent.body.as_deref().unwrap_or_default()))
+            .collect();
+
+        // Accumulate the TF-IDF scores of each WR to find the topics of the year
+        let mut topic_scores: HashMap<String, f64> = HashMap::new();
+        for scores in text::tfidf(&wr_tokens) {
+            for (term, score) in scores {
+                *topic_scores.entry(term).or_insert(0.0) += score;
+            }
+        }
+
+        // Concatenate the WRs of each month into a single document
+        let mut month_tokens: HashMap<u32, Vec<String>> = HashMap::new();
+        for (wr, tokens) in wrs.wrs.iter().zip(wr_tokens.iter()) {
+            month_tokens
+                .entry(wr.sent.env.date.month())
+                .or_default()
+                .extend(tokens.iter().cloned());
+        }
+        let (months, month_docs): (Vec<u32>, Vec<Vec<String>>) = month_tokens.into_iter().unzip();
+        let month_keywords = months
+            .into_iter()
+            .zip(text::tfidf(&month_docs))
+            .map(|(month, scores)| (month, text::top_scores(scores, num_top_words)))
+            .collect();
+
+        let words_series: Vec<(DateTime<FixedOffset>, f64)> = wrs
+            .wrs
+            .iter()
+            .map(|wr| (wr.sent.env.date, wr.num_words() as f64))
+            .collect();
+
+        // Split the WRs into the sections of the template
+        let parser = SectionParser::new(&config.sections);
+        let wr_sections: Vec<_> = wrs
+            .wrs
+            .iter()
+            .map(|wr| parser.parse(wr.sent.body.as_deref().unwrap_or_default()))
+            .collect();
+        let mut section_item_counts = HashMap::new();
+        let mut month_section_histogram = HashMap::new();
+        let mut section_trends = HashMap::new();
+        for name in config.sections.keys() {
+            let mut hist: HashMap<u32, u32> = (1..=12).map(|month| (month, 0)).collect();
+            let mut series = Vec::new();
+            for (wr, sections) in wrs.wrs.iter().zip(wr_sections.iter()) {
+                let num_items = sections::count_items(sections, name);
+                hist.entry(wr.sent.env.date.month())
+                    .and_modify(|e| *e += num_items as u32);
+                series.push((wr.sent.env.date, num_items as f64));
+            }
+            section_item_counts.insert(name.clone(), hist.values().sum::<u32>() as usize);
+            month_section_histogram.insert(name.clone(), hist);
+            section_trends.insert(name.clone(), Trend::from_series(&series));
+        }
+
+        // Track the TODO items that reappear in consecutive WRs
+        let dated_sections: Vec<_> = wrs
+            .wrs
+            .iter()
+            .zip(wr_sections.iter())
+            .map(|(wr, sections)| (wr.sent.env.date, sections.as_slice()))
+            .collect();
+        let mut carry_overs = carryover::detect_carry_overs(
+            &dated_sections,
+            &config.carry_over.section,
+            config.carry_over.similarity,
+        );
+        let num_carry_overs = carry_overs.len();
+        carry_overs.truncate(10);
+
+        // Score the sentiment of the WRs and their replies
+        let analyzer = SentimentAnalyzer::new();
+        let wr_moods: Vec<Mood> = wrs
+            .wrs
+            .iter()
+            .map(|wr| Mood {
+                date: wr.sent.env.date.format("%Y-%m-%d").to_string(),
+                subject: wr.sent.env.subject.clone(),
+                score: analyzer.score(wr.sent.body.as_deref().unwrap_or_default()),
+            })
+            .collect();
+        let reply_moods: Vec<f64> = wrs
+            .wrs
+            .iter()
+            .filter_map(|wr| wr.reply.as_ref())
+            .filter_map(|reply| reply.body.as_deref())
+            .map(|body| analyzer.score(body))
+            .collect();
+        let mut month_mood: HashMap<u32, Vec<f64>> = HashMap::new();
+        for (wr, mood) in wrs.wrs.iter().zip(wr_moods.iter()) {
+            month_mood
+                .entry(wr.sent.env.date.month())
+                .or_default()
+                .push(mood.score);
+        }
+        let month_mood = (1..=12)
+            .map(|month| {
+                // A month without WRs has no mood, rather than a neutral one
+                let scores = month_mood.get(&month);
+                (
+                    month,
+                    scores.map(|scores| Summary::from_values(scores).mean),
+                )
+            })
+            .collect();
+        let mood_delays: Vec<(f64, f64)> = wrs
+            .wrs
+            .iter()
+            .zip(wr_moods.iter())
+            .filter_map(|(wr, mood)| {
+                let reply = wr.reply.as_ref()?;
+                let delay = reply.env.date.signed_duration_since(wr.sent.env.date);
+                Some((mood.score, delay.num_seconds() as f64 / 3600.0))
+            })
+            .collect();
+
+        // Analyze the writing style of the WRs
+        let styles: Vec<WritingStyle> = wrs
+            .wrs
+            .iter()
+            .filter_map(|wr| wr.sent.body.as_deref())
+            .map(WritingStyle::from_text)
+            .collect();
+        let style_summary = |metric: fn(&WritingStyle) -> f64| {
+            Summary::from_values(&styles.iter().map(metric).collect::<Vec<_>>())
+        };
+
+        let cc_names = wrs.cc_names();
+        let (new_ccs, dropped_ccs) = cc_changes(wrs, &cc_names);
+
+        let mut stats = Stats {
+            schema_version: SCHEMA_VERSION,
+            year: config.query.year,
+            author: config.query.from.clone(),
             num_wrs: wrs.num_wrs(),
+            longest_streak: wrs.longest_streak(),
             num_replied_wrs: wrs.num_replied_wrs(),
             ratio_replied_wrs: wrs.ratio_replied_wrs(),
             num_words: wrs.num_words(),
-            avg_wr_delay: wrs.avg_wr_delay(),
-            avg_reply_delay: wrs.avg_reply_delay(),
+            words_per_wr: Summary::from_values(&wrs.words_per_wr()),
+            word_counts: wrs.wrs.iter().map(WordCount::from_wr).collect(),
+            month_words_histogram: wrs.month_words_histogram(),
+            words_trend: Trend::from_series(&words_series),
+            longest_wr: wrs.longest_wr().map(WordCount::from_wr),
+            shortest_wr: wrs.shortest_wr().map(WordCount::from_wr),
+            wr_delay: Summary::from_values(&wrs.wr_delays()),
+            reply_delay: Summary::from_values(&wrs.reply_delays()),
+            num_attachments: wrs.num_attachments(),
+            attachments_per_wr: Summary::from_values(&wrs.attachments_per_wr()),
+            attachment_megabytes: wrs.attachment_megabytes(),
+            attachment_type_histogram: wrs.attachment_type_histogram(),
             weekday_wr_histogram: wrs.weekday_wr_histogram(),
             weekday_reply_histogram: wrs.weekday_reply_histogram(),
             hour_wr_histogram: wrs.hour_wr_histogram(),
             hour_reply_histogram: wrs.hour_reply_histogram(),
+            weekday_hour_reply_heatmap: wrs.weekday_hour_reply_heatmap(),
             cc_histogram: wrs.cc_histogram(),
+            month_cc_histogram: wrs.month_cc_histogram(),
+            new_ccs,
+            dropped_ccs,
+            cc_names,
+            project_histogram: wrs.project_histogram(),
+            month_project_histogram: wrs.month_project_histogram(),
+            wr_mood: Summary::from_values(&wr_moods.iter().map(|m| m.score).collect::<Vec<_>>()),
+            reply_mood: Summary::from_values(&reply_moods),
+            month_mood,
+            mood_reply_delay_correlation: correlation(&mood_delays),
+            most_upbeat_wr: wr_moods
+                .iter()
+                .max_by(|a, b| a.score.total_cmp(&b.score))
+                .cloned(),
+            most_gloomy_wr: wr_moods
+                .iter()
+                .min_by(|a, b| a.score.total_cmp(&b.score))
+                .cloned(),
+            reading_ease: style_summary(|s| s.reading_ease),
+            sentence_length: style_summary(|s| s.sentence_length),
+            bullet_ratio: style_summary(|s| s.bullet_ratio),
+            num_emojis: styles.iter().map(|s| s.num_emojis).sum(),
+            num_exclamation_marks: styles.iter().map(|s| s.num_exclamation_marks).sum(),
+            num_links: styles.iter().map(|s| s.num_links).sum(),
+            section_item_counts,
+            month_section_histogram,
+            section_trends,
+            num_carry_overs,
+            longest_carry_over: carry_overs.first().cloned(),
+            carry_overs,
+            top_words: text::top_terms(&wr_tokens, num_top_words),
+            top_topics: text::top_scores(topic_scores, num_top_words),
+            month_keywords,
+            achievements: Vec::new(),
+        };
+        stats.achievements = achievements::evaluate(wrs, &stats, &config.achievements);
+        stats
+    }
+
+    // The display names of the people that were CC'd the most, with their counts
+    pub fn top_ccs(&self, n: usize) -> Vec<(String, u32)> {
+        let mut ccs: Vec<(&String, &u32)> = self.cc_histogram.iter().collect();
+        ccs.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        ccs.into_iter()
+            .take(n)
+            .map(|(email, count)| {
+                let name = match self.cc_names.get(email) {
+                    Some(name) => name.clone(),
+                    None => email.split('@').next().unwrap_or(email).to_string(),
+                };
+                (name, *count)
+            })
+            .collect()
+    }
+
+    // The weekday on which the most WRs were sent
+    pub fn favourite_weekday(&self) -> Option<Weekday> {
+        let (day, _) = self
+            .weekday_wr_histogram
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))?;
+        Weekday::try_from(*day as u8).ok()
+    }
+
+    pub fn read_from_file(file_path: &str) -> Result<Self> {
+        let contents = fs::read_to_string(file_path)?;
+        let value: serde_json::Value = serde_json::from_str(&contents)?;
+
+        // Files of another version may have a different meaning of existing fields
+        let version = value["schema_version"].as_u64().unwrap_or(0);
+        if version > SCHEMA_VERSION as u64 {
+            return Err(WrError::SerializationError(format!(
+                "{} has schema version {}, but only version {} is supported",
+                file_path, version, SCHEMA_VERSION
+            )));
+        }
+        if version < SCHEMA_VERSION as u64 {
+            return Err(WrError::SerializationError(format!(
+                "{} has schema version {} and has to be regenerated with this version of WRapped",
+                file_path, version
+            )));
         }
+        validate(&value)
+            .map_err(|e| WrError::SerializationError(format!("{} is invalid: {}", file_path, e)))?;
+
+        let stats = serde_json::from_value(value)?;
+        Ok(stats)
     }
 
     pub fn write_to_file(&self, file_path: &str) -> Result<()> {
-        // Create the directory path if it doesn't exist
-        let path = Path::new(file_path);
-        if let Some(dir_path) = path.parent() {
-            fs::create_dir_all(dir_path)?;
-        }
+        write_json_file(self, file_path)
+    }
+}
 
-        // Serialize and write to the file
-        let serialized = serde_json::to_string_pretty(&self)
-            .map_err(|e| WrError::SerializationError(e.to_string()))?;
-        let mut file = File::create(path)?;
-        file.write_all(serialized.as_bytes())?;
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+// The JSON Schema of the stats file
+pub fn json_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(Stats)).unwrap()
+}
+
+// Check a stats file against the JSON Schema and describe the first errors
+fn validate(value: &serde_json::Value) -> std::result::Result<(), String> {
+    let schema = json_schema();
+    let validator = jsonschema::validator_for(&schema).map_err(|e| e.to_string())?;
+    let errors: Vec<String> = validator
+        .iter_errors(value)
+        .take(5)
+        .map(|e| format!("{} at \"{}\"", e, e.instance_path))
+        .collect();
+    if errors.is_empty() {
         Ok(())
+    } else {
+        Err(errors.join(", "))
+    }
+}
+
+pub fn write_json_file<T: Serialize>(value: &T, file_path: &str) -> Result<()> {
+    // Create the directory path if it doesn't exist
+    let path = Path::new(file_path);
+    if let Some(dir_path) = path.parent() {
+        fs::create_dir_all(dir_path)?;
+    }
+
+    // Serialize and write to the file
+    let serialized = serde_json::to_string_pretty(value)
+        .map_err(|e| WrError::SerializationError(e.to_string()))?;
+    let mut file = File::create(path)?;
+    file.write_all(serialized.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use crate::wr::tests::wr_sent_at;
+
+    #[test]
+    fn summary_of_no_values_is_zero() {
+        let summary = Summary::from_values(&[]);
+        assert_eq!(summary.count, 0);
+        assert_eq!(summary.mean, 0.0);
+        assert_eq!(summary.median, 0.0);
+        assert_eq!(summary.p90, 0.0);
+    }
+
+    #[test]
+    fn summary_of_a_single_value() {
+        let summary = Summary::from_values(&[4.0]);
+        assert_eq!(summary.count, 1);
+        for value in [
+            summary.mean,
+            summary.median,
+            summary.p90,
+            summary.min,
+            summary.max,
+        ] {
+            assert_eq!(value, 4.0);
+        }
+    }
+
+    #[test]
+    fn summary_interpolates_percentiles() {
+        // The input does not have to be sorted
+        let summary = Summary::from_values(&[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert!((summary.p90 - 3.7).abs() < 1e-9);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 4.0);
+    }
+
+    fn weekly_series(values: &[f64]) -> Vec<(DateTime<FixedOffset>, f64)> {
+        let first = DateTime::parse_from_rfc3339("2023-03-03T17:00:00+01:00").unwrap();
+        values
+            .iter()
+            .enumerate()
+            .map(|(week, value)| (first + Duration::weeks(week as i64), *value))
+            .collect()
+    }
+
+    #[test]
+    fn trend_needs_two_points_in_time() {
+        for series in [weekly_series(&[]), weekly_series(&[5.0])] {
+            let trend = Trend::from_series(&series);
+            assert_eq!(trend.slope_per_week, 0.0);
+            assert_eq!(trend.relative_change, 0.0);
+        }
+    }
+
+    #[test]
+    fn trend_fits_a_line() {
+        let trend = Trend::from_series(&weekly_series(&[10.0, 20.0, 30.0]));
+        assert!((trend.slope_per_week - 10.0).abs() < 1e-9);
+        assert!((trend.relative_change - 2.0).abs() < 1e-9);
+
+        let trend = Trend::from_series(&weekly_series(&[30.0, 20.0, 10.0]));
+        assert!((trend.slope_per_week + 10.0).abs() < 1e-9);
+        assert!((trend.relative_change + 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flat_trend_has_no_change() {
+        let trend = Trend::from_series(&weekly_series(&[7.0, 7.0, 7.0, 7.0]));
+        assert_eq!(trend.slope_per_week, 0.0);
+        assert_eq!(trend.relative_change, 0.0);
+    }
+
+    #[test]
+    fn months_without_wrs_have_no_mood() {
+        let mut wrs = WRs::new();
+        wrs.wrs.push(wr_sent_at("2023-03-03T17:00:00+01:00"));
+        let stats = Stats::from_wrs(&wrs, &test_config("me@example.com"));
+        assert_eq!(stats.month_mood[&3], Some(0.0));
+        assert_eq!(stats.month_mood[&4], None);
+        assert_eq!(stats.month_mood.len(), 12);
     }
 }
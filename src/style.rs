@@ -0,0 +1,117 @@
+use crate::sections;
+use crate::text;
+
+const EMOTICONS: &[&str] = &[
+    ":)", ":-)", ";)", ";-)", ":D", ":-D", ":(", ":-(", ":P", ":-P",
+];
+
+#[derive(Debug, Default, Clone)]
+pub struct WritingStyle {
+    // The Flesch reading ease, higher is easier to read
+    pub reading_ease: f64,
+    // The average number of words per sentence
+    pub sentence_length: f64,
+    // The ratio of lines that are bullet points
+    pub bullet_ratio: f64,
+    // The number of emojis and emoticons
+    pub num_emojis: usize,
+    // The number of exclamation marks
+    pub num_exclamation_marks: usize,
+    // The number of links
+    pub num_links: usize,
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F300..=0x1FAFF | 0x2600..=0x27BF)
+}
+
+fn is_link(word: &str) -> bool {
+    word.starts_with("http://") || word.starts_with("https://") || word.starts_with("www.")
+}
+
+// Estimate the syllables of a word by counting groups of vowels
+fn num_syllables(word: &str) -> usize {
+    let vowels = "aeiouyäöü";
+    let mut count = 0;
+    let mut prev_vowel = false;
+    for c in word.chars() {
+        let vowel = vowels.contains(c);
+        if vowel && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = vowel;
+    }
+    // A trailing "e" is usually silent in English, e.g. "make"
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+impl WritingStyle {
+    pub fn from_text(body: &str) -> Self {
+        // Only consider the lines that were written in this mail
+        let lines: Vec<&str> = body
+            .lines()
+            .take_while(|line| *line != "-- ")
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('>'))
+            .collect();
+        if lines.is_empty() {
+            return WritingStyle::default();
+        }
+
+        let num_bullets = lines
+            .iter()
+            .filter(|line| sections::strip_bullet(line).is_some())
+            .count();
+        let tokens: Vec<&str> = lines.iter().flat_map(|l| l.split_whitespace()).collect();
+        let num_links = tokens.iter().filter(|t| is_link(t)).count();
+        let num_emojis = lines
+            .iter()
+            .flat_map(|l| l.chars())
+            .filter(|c| is_emoji(*c))
+            .count()
+            + tokens.iter().filter(|t| EMOTICONS.contains(t)).count();
+        let num_exclamation_marks = lines.iter().map(|l| l.matches('!').count()).sum();
+
+        // Every line ends a sentence, even if it is a bullet without punctuation
+        let mut num_sentences = 0;
+        let mut words: Vec<String> = Vec::new();
+        for line in lines.iter() {
+            let line = sections::strip_bullet(line).unwrap_or(line);
+            let prose: Vec<&str> = line.split_whitespace().filter(|t| !is_link(t)).collect();
+            // A sentence ends with punctuation that is followed by a space, e.g. not in "3.5"
+            let sentences = prose
+                .iter()
+                .filter(|t| t.ends_with(['.', '!', '?']))
+                .count();
+            let trailing = prose.last().is_some_and(|t| !t.ends_with(['.', '!', '?']));
+            num_sentences += (sentences + trailing as usize).max(1);
+            words.extend(prose.iter().flat_map(|t| text::words(t)));
+        }
+        if words.is_empty() {
+            return WritingStyle {
+                bullet_ratio: num_bullets as f64 / lines.len() as f64,
+                num_emojis,
+                num_exclamation_marks,
+                num_links,
+                ..WritingStyle::default()
+            };
+        }
+
+        let num_words = words.len() as f64;
+        let sentence_length = num_words / num_sentences as f64;
+        let syllables_per_word =
+            words.iter().map(|w| num_syllables(w)).sum::<usize>() as f64 / num_words;
+
+        WritingStyle {
+            reading_ease: 206.835 - 1.015 * sentence_length - 84.6 * syllables_per_word,
+            sentence_length,
+            bullet_ratio: num_bullets as f64 / lines.len() as f64,
+            num_emojis,
+            num_exclamation_marks,
+            num_links,
+        }
+    }
+}
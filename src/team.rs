@@ -0,0 +1,152 @@
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::stats::{self, Stats};
+
+#[derive(Debug, Serialize)]
+pub struct TeamMember {
+    // The address the member sent the WRs from
+    pub name: String,
+    // The number of WRs of the member
+    pub num_wrs: usize,
+    // The ratio of WRs of the member that were replied to
+    pub ratio_replied_wrs: f64,
+    // The number of words the member wrote
+    pub num_words: usize,
+    // The largest number of consecutive weeks with a WR of the member
+    pub longest_streak: usize,
+    // The average delay of the WRs of the member in days since Friday, 0 if always on Friday
+    pub avg_wr_delay: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardEntry {
+    // The address the member sent the WRs from
+    pub name: String,
+    // The value the leaderboard is ranked by
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TeamStats {
+    // The years of the WRs of the members
+    pub years: Vec<u32>,
+    // The number of members in the team
+    pub num_members: usize,
+    // The number of WRs of the whole team
+    pub num_wrs: usize,
+    // The number of words the whole team wrote
+    pub num_words: usize,
+    // The ratio of the WRs of the team that were replied to
+    pub ratio_replied_wrs: f64,
+    // The statistics of each member
+    pub members: Vec<TeamMember>,
+    // The members ranked by the number of WRs
+    pub most_wrs: Vec<LeaderboardEntry>,
+    // The members ranked by the longest streak of weekly WRs
+    pub longest_streak: Vec<LeaderboardEntry>,
+    // The members ranked by the average delay of their WRs, smallest first
+    pub most_punctual: Vec<LeaderboardEntry>,
+}
+
+fn leaderboard(
+    members: &[TeamMember],
+    value: fn(&TeamMember) -> f64,
+    ascending: bool,
+) -> Vec<LeaderboardEntry> {
+    let mut entries: Vec<LeaderboardEntry> = members
+        .iter()
+        .filter(|member| member.num_wrs > 0)
+        .map(|member| LeaderboardEntry {
+            name: member.name.clone(),
+            value: value(member),
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        let order = a.value.total_cmp(&b.value);
+        let order = if ascending { order } else { order.reverse() };
+        order.then_with(|| a.name.cmp(&b.name))
+    });
+    entries
+}
+
+impl TeamStats {
+    pub fn from_stats(team: &[Stats]) -> Self {
+        let members: Vec<TeamMember> = team
+            .iter()
+            .map(|stats| TeamMember {
+                name: stats.author.clone(),
+                num_wrs: stats.num_wrs,
+                ratio_replied_wrs: stats.ratio_replied_wrs,
+                num_words: stats.num_words,
+                longest_streak: stats.longest_streak,
+                avg_wr_delay: stats.wr_delay.mean,
+            })
+            .collect();
+
+        let mut years: Vec<u32> = team.iter().map(|stats| stats.year).collect();
+        years.sort();
+        years.dedup();
+
+        let num_wrs = team.iter().map(|stats| stats.num_wrs).sum();
+        let num_replied_wrs: usize = team.iter().map(|stats| stats.num_replied_wrs).sum();
+        let ratio_replied_wrs = match num_wrs {
+            0 => 0.0,
+            _ => num_replied_wrs as f64 / num_wrs as f64,
+        };
+
+        TeamStats {
+            years,
+            num_members: members.len(),
+            num_wrs,
+            num_words: team.iter().map(|stats| stats.num_words).sum(),
+            ratio_replied_wrs,
+            most_wrs: leaderboard(&members, |m| m.num_wrs as f64, false),
+            longest_streak: leaderboard(&members, |m| m.longest_streak as f64, false),
+            most_punctual: leaderboard(&members, |m| m.avg_wr_delay, true),
+            members,
+        }
+    }
+
+    pub fn write_to_file(&self, file_path: &str) -> Result<()> {
+        stats::write_json_file(self, file_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::test_config;
+    use crate::wr::tests::wr_sent_at;
+    use crate::wr::WRs;
+
+    fn member_stats(from: &str, dates: &[&str]) -> Stats {
+        let mut wrs = WRs::new();
+        wrs.wrs.extend(dates.iter().map(|date| wr_sent_at(date)));
+        Stats::from_wrs(&wrs, &test_config(from))
+    }
+
+    #[test]
+    fn friday_wrs_are_most_punctual() {
+        let team = [
+            member_stats(
+                "saturday@example.com",
+                &["2023-03-04T10:00:00+01:00", "2023-03-11T10:00:00+01:00"],
+            ),
+            member_stats(
+                "friday@example.com",
+                &["2023-03-03T17:00:00+01:00", "2023-03-10T17:00:00+01:00"],
+            ),
+        ];
+        let team_stats = TeamStats::from_stats(&team);
+        let ranking: Vec<(&str, f64)> = team_stats
+            .most_punctual
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.value))
+            .collect();
+        assert_eq!(
+            ranking,
+            [("friday@example.com", 0.0), ("saturday@example.com", 1.0)]
+        );
+    }
+}
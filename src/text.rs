@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::TextConfig;
+
+const STOPWORDS_EN: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "am", "an", "and", "any",
+    "are", "aren't", "as", "at", "be", "because", "been", "before", "being", "below", "best",
+    "between", "both", "but", "by", "can", "cannot", "could", "did", "do", "does", "doing", "done",
+    "down", "during", "each", "few", "for", "from", "further", "get", "got", "had", "has", "have",
+    "having", "he", "her", "here", "hers", "herself", "him", "himself", "his", "how", "i", "if",
+    "in", "into", "is", "it", "it's", "its", "itself", "just", "last", "let", "like", "me", "more",
+    "most", "my", "myself", "next", "no", "nor", "not", "now", "of", "off", "on", "once", "only",
+    "or", "other", "our", "ours", "out", "over", "own", "regards", "same", "she", "should", "so",
+    "some", "still", "such", "than", "thanks", "that", "the", "their", "theirs", "them", "then",
+    "there", "these", "they", "this", "those", "through", "to", "too", "under", "until", "up",
+    "us", "very", "was", "we", "week", "well", "were", "what", "when", "where", "which", "while",
+    "who", "whom", "why", "will", "with", "would", "you", "your", "yours", "yourself",
+];
+
+const STOPWORDS_DE: &[&str] = &[
+    "aber", "alle", "allem", "allen", "aller", "alles", "als", "also", "am", "an", "ander",
+    "andere", "anderen", "anderer", "anderes", "auch", "auf", "aus", "bei", "bin", "bis", "bist",
+    "da", "damit", "dann", "das", "dass", "dein", "deine", "dem", "den", "denn", "der", "des",
+    "dich", "die", "dies", "diese", "diesem", "diesen", "dieser", "dieses", "dir", "doch", "dort",
+    "du", "durch", "ein", "eine", "einem", "einen", "einer", "eines", "er", "es", "etwas", "euch",
+    "euer", "eure", "für", "gegen", "gewesen", "gruss", "gruß", "grüsse", "grüße", "hab", "habe",
+    "haben", "hat", "hatte", "hier", "hin", "hinter", "ich", "ihm", "ihn", "ihr", "ihre", "im",
+    "in", "indem", "ins", "ist", "jede", "jedem", "jeden", "jeder", "jedes", "jetzt", "kann",
+    "kein", "keine", "können", "machen", "man", "manche", "mein", "meine", "mich", "mir", "mit",
+    "muss", "nach", "nicht", "nichts", "noch", "nun", "nur", "ob", "oder", "ohne", "sehr", "sein",
+    "seine", "sich", "sie", "sind", "so", "solche", "soll", "sondern", "um", "und", "uns", "unser",
+    "unter", "viel", "vom", "von", "vor", "war", "waren", "was", "weil", "welche", "wenn", "wer",
+    "werde", "werden", "wie", "wieder", "will", "wir", "wird", "woche", "zu", "zum", "zur",
+    "zwischen",
+];
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TermCount {
+    /// The term
+    pub term: String,
+    /// The number of occurrences of the term
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TermScore {
+    /// The term
+    pub term: String,
+    /// The TF-IDF score of the term
+    pub score: f64,
+}
+
+pub struct Tokenizer {
+    // The words that are dropped from the tokens
+    stopwords: HashSet<String>,
+    // The minimum number of characters of a token
+    min_length: usize,
+}
+
+impl Tokenizer {
+    pub fn new(config: &TextConfig) -> Self {
+        let mut stopwords: HashSet<String> = HashSet::new();
+        for language in config.stopword_languages.iter() {
+            let words = match language.as_str() {
+                "en" => STOPWORDS_EN,
+                "de" => STOPWORDS_DE,
+                _ => &[],
+            };
+            stopwords.extend(words.iter().map(|w| w.to_string()));
+        }
+        stopwords.extend(config.stopwords.iter().map(|w| w.to_lowercase()));
+        Tokenizer {
+            stopwords,
+            min_length: config.min_word_length,
+        }
+    }
+
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        words(text)
+            .filter(|word| word.chars().count() >= self.min_length)
+            .filter(|word| !word.chars().all(|c| c.is_numeric()))
+            .filter(|word| !self.stopwords.contains(word))
+            .collect()
+    }
+}
+
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.lines()
+        // Skip quoted text of previous mails
+        .filter(|line| !line.trim_start().starts_with('>'))
+        .flat_map(|line| line.split(|c: char| !(c.is_alphanumeric() || c == '\'')))
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| !word.is_empty())
+}
+
+pub fn top_terms(docs: &[Vec<String>], n: usize) -> Vec<TermCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for term in docs.iter().flatten() {
+        *counts.entry(term.as_str()).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .sorted_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)))
+        .take(n)
+        .map(|(term, count)| TermCount {
+            term: term.to_string(),
+            count,
+        })
+        .collect()
+}
+
+pub fn tfidf(docs: &[Vec<String>]) -> Vec<HashMap<String, f64>> {
+    // Count in how many documents each term appears
+    let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+    for doc in docs.iter() {
+        for term in doc.iter().map(|t| t.as_str()).unique() {
+            *doc_freq.entry(term).or_insert(0) += 1;
+        }
+    }
+
+    let num_docs = docs.len() as f64;
+    docs.iter()
+        .map(|doc| {
+            let mut term_freq: HashMap<&str, usize> = HashMap::new();
+            for term in doc.iter() {
+                *term_freq.entry(term.as_str()).or_insert(0) += 1;
+            }
+            term_freq
+                .into_iter()
+                .map(|(term, count)| {
+                    let tf = count as f64 / doc.len() as f64;
+                    // Smoothed IDF, such that terms in every document still have a weight
+                    let idf = (1.0 + num_docs / doc_freq[term] as f64).ln();
+                    (term.to_string(), tf * idf)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn top_scores(scores: HashMap<String, f64>, n: usize) -> Vec<TermScore> {
+    scores
+        .into_iter()
+        .sorted_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)))
+        .take(n)
+        .map(|(term, score)| TermScore { term, score })
+        .collect()
+}
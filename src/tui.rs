@@ -0,0 +1,224 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{BarChart, Block, List, ListItem, ListState, Paragraph, Sparkline, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::error::Result;
+use crate::stats::Stats;
+
+const TABS: [&str; 4] = ["Weekday", "Hour", "CC", "WRs"];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+struct App {
+    stats: Stats,
+    // The index of the selected tab
+    tab: usize,
+    // The selected WR in the WR browser
+    wr_state: ListState,
+}
+
+impl App {
+    fn new(mut stats: Stats) -> Self {
+        // Browse the WRs in chronological order
+        stats.word_counts.sort_by(|a, b| a.date.cmp(&b.date));
+        let mut wr_state = ListState::default();
+        if !stats.word_counts.is_empty() {
+            wr_state.select(Some(0));
+        }
+        App {
+            stats,
+            tab: 0,
+            wr_state,
+        }
+    }
+
+    // Returns false if the user wants to quit
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.tab = (self.tab + 1) % TABS.len();
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.tab = (self.tab + TABS.len() - 1) % TABS.len();
+            }
+            KeyCode::Char(c @ '1'..='4') => self.tab = c as usize - '1' as usize,
+            KeyCode::Down | KeyCode::Char('j') => self.wr_state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.wr_state.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => self.wr_state.select_first(),
+            KeyCode::End | KeyCode::Char('G') => self.wr_state.select_last(),
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let title = format!(" WRapped {} ", self.stats.year);
+        let tabs = Tabs::new(TABS)
+            .block(Block::bordered().title(title))
+            .select(self.tab)
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_widget(tabs, header);
+
+        match self.tab {
+            0 => self.draw_weekdays(frame, body),
+            1 => self.draw_hours(frame, body),
+            2 => self.draw_ccs(frame, body),
+            _ => self.draw_wrs(frame, body),
+        }
+
+        let help = "←/→ switch tab  ↑/↓ browse WRs  q quit";
+        frame.render_widget(
+            Paragraph::new(help).style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn draw_weekdays(&self, frame: &mut Frame, area: Rect) {
+        let data: Vec<(&str, u64)> = WEEKDAYS
+            .iter()
+            .enumerate()
+            .map(|(day, name)| {
+                let count = self.stats.weekday_wr_histogram.get(&(day as u32));
+                (*name, count.copied().unwrap_or(0) as u64)
+            })
+            .collect();
+        let chart = BarChart::default()
+            .block(Block::bordered().title(" WRs per weekday "))
+            .data(&data)
+            .bar_width(bar_width(area, data.len()))
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Red))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Red));
+        frame.render_widget(chart, area);
+    }
+
+    fn draw_hours(&self, frame: &mut Frame, area: Rect) {
+        let labels: Vec<String> = (0..24).map(|hour| format!("{:02}", hour)).collect();
+        let data: Vec<(&str, u64)> = labels
+            .iter()
+            .enumerate()
+            .map(|(hour, label)| {
+                let count = self.stats.hour_wr_histogram.get(&(hour as u32));
+                (label.as_str(), count.copied().unwrap_or(0) as u64)
+            })
+            .collect();
+        let chart = BarChart::default()
+            .block(Block::bordered().title(" WRs per hour "))
+            .data(&data)
+            .bar_width(bar_width(area, data.len()))
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Yellow))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+        frame.render_widget(chart, area);
+    }
+
+    fn draw_ccs(&self, frame: &mut Frame, area: Rect) {
+        // One row per person, leaving room for the border
+        let ccs = self.stats.top_ccs(area.height.saturating_sub(2) as usize);
+        if ccs.is_empty() {
+            let empty = Paragraph::new("Nobody was in CC").block(Block::bordered());
+            frame.render_widget(empty, area);
+            return;
+        }
+        let data: Vec<(&str, u64)> = ccs
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count as u64))
+            .collect();
+        let chart = BarChart::default()
+            .block(Block::bordered().title(" Most CC'd "))
+            .direction(Direction::Horizontal)
+            .data(&data)
+            .bar_width(1)
+            .bar_gap(0)
+            .bar_style(Style::default().fg(Color::Magenta))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Magenta));
+        frame.render_widget(chart, area);
+    }
+
+    fn draw_wrs(&mut self, frame: &mut Frame, area: Rect) {
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(area);
+
+        let items: Vec<ListItem> = self
+            .stats
+            .word_counts
+            .iter()
+            .map(|wr| ListItem::new(format!("{}  {}", wr.date, wr.subject)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" {} WRs ", self.stats.num_wrs)))
+            .highlight_style(Style::default().fg(Color::Black).bg(Color::Yellow))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.wr_state);
+
+        let [detail_area, sparkline_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(6)]).areas(detail_area);
+        let selected = self
+            .wr_state
+            .selected()
+            .and_then(|i| self.stats.word_counts.get(i));
+        let lines = match selected {
+            Some(wr) => vec![
+                Line::from(format!("Date:    {}", wr.date)),
+                Line::from(format!("Subject: {}", wr.subject)),
+                Line::from(format!("Words:   {}", wr.num_words)),
+                Line::from(format!("Average: {:.0}", self.stats.words_per_wr.mean)),
+            ],
+            None => vec![Line::from("No WRs")],
+        };
+        let detail = Paragraph::new(lines).block(Block::bordered().title(" WR "));
+        frame.render_widget(detail, detail_area);
+
+        let words: Vec<u64> = self
+            .stats
+            .word_counts
+            .iter()
+            .map(|wr| wr.num_words as u64)
+            .collect();
+        let sparkline = Sparkline::default()
+            .block(Block::bordered().title(" Words over the year "))
+            .data(&words)
+            .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(sparkline, sparkline_area);
+    }
+}
+
+// Spread the bars over the width of the chart
+fn bar_width(area: Rect, num_bars: usize) -> u16 {
+    let inner = area.width.saturating_sub(2) as usize;
+    ((inner / num_bars.max(1)).saturating_sub(1)).max(1) as u16
+}
+
+fn run_app(terminal: &mut DefaultTerminal, mut app: App) -> Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !app.handle_key(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+pub fn run(stats: Stats) -> Result<()> {
+    let mut terminal = ratatui::init();
+    // Always restore the terminal, even if drawing failed
+    let result = run_app(&mut terminal, App::new(stats));
+    ratatui::restore();
+    result
+}
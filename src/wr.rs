@@ -1,8 +1,9 @@
-use chrono::{Datelike, Timelike};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, Timelike};
 use log::info;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::mail::Mail;
+use crate::config::ProjectConfig;
+use crate::mail::{Address, Mail};
 
 pub fn merge_wrs(wrs: &[Mail], wrs_re: &[Mail]) -> WRs {
     let mut merged_wrs = WRs::new();
@@ -35,16 +36,40 @@ pub struct WR {
     pub sent: Mail,
     // The Envelope of the WR reply that was received, if any
     pub reply: Option<Mail>,
+    // The projects that are mentioned in the WR
+    pub projects: Vec<String>,
 }
 
 impl WR {
     pub fn new(sent: Mail, reply: Option<Mail>) -> Self {
-        WR { sent, reply }
+        WR {
+            sent,
+            reply,
+            projects: Vec::new(),
+        }
+    }
+
+    pub fn mentions(&self, keyword: &str) -> bool {
+        let keyword = keyword.to_lowercase();
+        let text = format!(
+            "{}\n{}",
+            self.sent.env.subject,
+            self.sent.body.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+
+        // Only match whole words, e.g. "snitch" should not match "snitches"
+        text.match_indices(keyword.as_str()).any(|(start, _)| {
+            let before = text[..start].chars().next_back();
+            let after = text[start + keyword.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
     }
 
     pub fn wr_delay(&self) -> i64 {
         let weekday = self.sent.env.date.weekday();
-        let days_since_friday = (weekday.num_days_from_monday() + 2) % 7;
+        // Friday is day 4 counted from Monday, so a WR sent on Friday is not late
+        let days_since_friday = (weekday.num_days_from_monday() + 3) % 7;
         days_since_friday as i64
     }
 
@@ -95,6 +120,25 @@ impl WRs {
         self.wrs.is_empty()
     }
 
+    pub fn tag_projects(&mut self, projects: &BTreeMap<String, ProjectConfig>) {
+        for wr in self.wrs.iter_mut() {
+            wr.projects = projects
+                .iter()
+                .filter(|(_, project)| project.keywords.iter().any(|k| wr.mentions(k)))
+                .map(|(name, _)| name.clone())
+                .collect();
+        }
+    }
+
+    pub fn apply_aliases(&mut self, aliases: &BTreeMap<String, String>) {
+        for wr in self.wrs.iter_mut() {
+            let mails = std::iter::once(&mut wr.sent).chain(wr.reply.as_mut());
+            for addr in mails.flat_map(|mail| mail.env.cc.iter_mut().flatten()) {
+                addr.apply_aliases(aliases);
+            }
+        }
+    }
+
     pub fn num_wrs(&self) -> usize {
         self.wrs.len()
     }
@@ -108,21 +152,107 @@ impl WRs {
     }
 
     pub fn ratio_replied_wrs(&self) -> f64 {
-        let num_replied_wrs: usize = self.num_replied_wrs();
         let num_wrs = self.num_wrs();
-        num_replied_wrs as f64 / num_wrs as f64
+        if num_wrs == 0 {
+            return 0.0;
+        }
+        self.num_replied_wrs() as f64 / num_wrs as f64
+    }
+
+    pub fn wr_delays(&self) -> Vec<f64> {
+        self.wrs.iter().map(|wr| wr.wr_delay() as f64).collect()
+    }
+
+    pub fn reply_delays(&self) -> Vec<f64> {
+        self.wrs
+            .iter()
+            .filter_map(|wr| wr.reply_delay())
+            .map(|delay| delay as f64)
+            .collect()
+    }
+
+    pub fn words_per_wr(&self) -> Vec<f64> {
+        self.wrs.iter().map(|wr| wr.num_words() as f64).collect()
+    }
+
+    pub fn month_words_histogram(&self) -> HashMap<u32, u32> {
+        let mut hist = HashMap::new();
+
+        for month in 1..=12 {
+            hist.insert(month, 0);
+        }
+        for wr in self.wrs.iter() {
+            let month = wr.sent.env.date.month();
+            hist.entry(month)
+                .and_modify(|e| *e += wr.num_words() as u32);
+        }
+        hist
+    }
+
+    pub fn longest_wr(&self) -> Option<&WR> {
+        self.wrs.iter().max_by_key(|wr| wr.num_words())
+    }
+
+    pub fn shortest_wr(&self) -> Option<&WR> {
+        self.wrs.iter().min_by_key(|wr| wr.num_words())
+    }
+
+    pub fn attachments_per_wr(&self) -> Vec<f64> {
+        self.wrs
+            .iter()
+            .map(|wr| wr.sent.attachments.len() as f64)
+            .collect()
+    }
+
+    pub fn num_attachments(&self) -> usize {
+        self.wrs.iter().map(|wr| wr.sent.attachments.len()).sum()
+    }
+
+    pub fn attachment_megabytes(&self) -> f64 {
+        let bytes: usize = self
+            .wrs
+            .iter()
+            .flat_map(|wr| wr.sent.attachments.iter())
+            .map(|attachment| attachment.size)
+            .sum();
+        bytes as f64 / 1e6
     }
 
-    pub fn avg_wr_delay(&self) -> f64 {
-        let num_replied_wrs = self.num_replied_wrs();
-        let wr_delay_sum: i64 = self.wrs.iter().map(|wr| wr.wr_delay()).sum();
-        wr_delay_sum as f64 / num_replied_wrs as f64
+    pub fn attachment_type_histogram(&self) -> HashMap<String, u32> {
+        let mut hist = HashMap::new();
+
+        for attachment in self.wrs.iter().flat_map(|wr| wr.sent.attachments.iter()) {
+            hist.entry(attachment.mimetype.to_string())
+                .and_modify(|e| *e += 1)
+                .or_insert(1);
+        }
+        hist
     }
 
-    pub fn avg_reply_delay(&self) -> f64 {
-        let num_replied_wrs = self.num_replied_wrs();
-        let reply_delay_sum: i64 = self.wrs.iter().filter_map(|wr| wr.reply_delay()).sum();
-        reply_delay_sum as f64 / num_replied_wrs as f64
+    pub fn longest_streak(&self) -> usize {
+        // The consecutive Mondays of the weeks in which a WR was sent
+        let mut weeks: Vec<NaiveDate> = self
+            .wrs
+            .iter()
+            .map(|wr| {
+                let date = wr.sent.env.date.date_naive();
+                date - Duration::days(date.weekday().num_days_from_monday() as i64)
+            })
+            .collect();
+        weeks.sort();
+        weeks.dedup();
+
+        let mut streak = 0;
+        let mut longest = 0;
+        for (i, week) in weeks.iter().enumerate() {
+            streak = match i {
+                0 => 1,
+                _ if *week - weeks[i - 1] == Duration::weeks(1) => streak + 1,
+                _ => 1,
+            };
+            longest = longest.max(streak);
+        }
+        longest
     }
 
     pub fn weekday_wr_histogram(&self) -> HashMap<u32, u32> {
@@ -146,8 +276,8 @@ impl WRs {
         }
         for wr in self.wrs.iter() {
             match wr.reply {
-                Some(_) => {
-                    let weekday = wr.sent.env.date.weekday();
+                Some(ref reply) => {
+                    let weekday = reply.env.date.weekday();
                     hist.entry(weekday as u32).and_modify(|e| *e += 1);
                 }
                 None => continue,
@@ -177,8 +307,8 @@ impl WRs {
         }
         for wr in self.wrs.iter() {
             match wr.reply {
-                Some(_) => {
-                    let hour = wr.sent.env.date.hour();
+                Some(ref reply) => {
+                    let hour = reply.env.date.hour();
                     hist.entry(hour).and_modify(|e| *e += 1);
                 }
                 None => continue,
@@ -187,23 +317,141 @@ impl WRs {
         hist
     }
 
-    pub fn cc_histogram(&self) -> HashMap<String, u32> {
+    pub fn weekday_hour_reply_heatmap(&self) -> Vec<Vec<u32>> {
+        // Indexed as `heatmap[weekday][hour]`, with Monday as weekday 0
+        let mut heatmap = vec![vec![0; 24]; 7];
+
+        for wr in self.wrs.iter() {
+            if let Some(ref reply) = wr.reply {
+                let weekday = reply.env.date.weekday().num_days_from_monday() as usize;
+                let hour = reply.env.date.hour() as usize;
+                heatmap[weekday][hour] += 1;
+            }
+        }
+        heatmap
+    }
+
+    pub fn project_histogram(&self) -> HashMap<String, u32> {
         let mut hist = HashMap::new();
 
         for wr in self.wrs.iter() {
-            match wr.sent.env.cc {
-                Some(ref cc) => {
-                    for addr in cc.iter() {
-                        if let Some(user) = &addr.user {
-                            hist.entry(user.to_string())
-                                .and_modify(|e| *e += 1)
-                                .or_insert(1);
-                        }
-                    }
+            for project in wr.projects.iter() {
+                hist.entry(project.to_string())
+                    .and_modify(|e| *e += 1)
+                    .or_insert(1);
+            }
+        }
+        hist
+    }
+
+    pub fn month_project_histogram(&self) -> HashMap<String, HashMap<u32, u32>> {
+        let mut hist: HashMap<String, HashMap<u32, u32>> = HashMap::new();
+
+        for wr in self.wrs.iter() {
+            let month = wr.sent.env.date.month();
+            for project in wr.projects.iter() {
+                hist.entry(project.to_string())
+                    .or_insert_with(|| (1..=12).map(|month| (month, 0)).collect())
+                    .entry(month)
+                    .and_modify(|e| *e += 1);
+            }
+        }
+        hist
+    }
+
+    fn cc_addresses(&self) -> impl Iterator<Item = (&WR, &Address)> {
+        self.wrs
+            .iter()
+            .flat_map(|wr| wr.sent.env.cc.iter().flatten().map(move |addr| (wr, addr)))
+    }
+
+    pub fn cc_histogram(&self) -> HashMap<String, u32> {
+        let mut hist = HashMap::new();
+
+        for (_, addr) in self.cc_addresses() {
+            if let Some(email) = addr.normalized_email() {
+                hist.entry(email).and_modify(|e| *e += 1).or_insert(1);
+            }
+        }
+        hist
+    }
+
+    pub fn cc_names(&self) -> HashMap<String, String> {
+        let mut names = HashMap::new();
+
+        // Keep the first display name that was used for an address
+        for (_, addr) in self.cc_addresses() {
+            if let (Some(email), Some(name)) = (addr.normalized_email(), &addr.name) {
+                if !name.trim().is_empty() {
+                    names
+                        .entry(email)
+                        .or_insert_with(|| name.trim().to_string());
                 }
-                None => continue,
-            };
+            }
+        }
+        names
+    }
+
+    pub fn month_cc_histogram(&self) -> HashMap<String, HashMap<u32, u32>> {
+        let mut hist: HashMap<String, HashMap<u32, u32>> = HashMap::new();
+
+        for (wr, addr) in self.cc_addresses() {
+            if let Some(email) = addr.normalized_email() {
+                hist.entry(email)
+                    .or_insert_with(|| (1..=12).map(|month| (month, 0)).collect())
+                    .entry(wr.sent.env.date.month())
+                    .and_modify(|e| *e += 1);
+            }
         }
         hist
     }
+
+    pub fn cc_dates(&self) -> HashMap<String, Vec<DateTime<FixedOffset>>> {
+        let mut dates: HashMap<String, Vec<DateTime<FixedOffset>>> = HashMap::new();
+
+        for (wr, addr) in self.cc_addresses() {
+            if let Some(email) = addr.normalized_email() {
+                dates.entry(email).or_default().push(wr.sent.env.date);
+            }
+        }
+        dates
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+    use crate::mail::{Envelope, Mail};
+
+    // A WR without reply, CCs or body that was sent at an RFC 3339 date
+    pub(crate) fn wr_sent_at(date: &str) -> WR {
+        let sent = Mail {
+            id: 0,
+            env: Envelope {
+                date: DateTime::parse_from_rfc3339(date).unwrap(),
+                subject: "WR".to_string(),
+                cc: None,
+                in_reply_to: None,
+                message_id: None,
+            },
+            body: None,
+            attachments: Vec::new(),
+        };
+        WR::new(sent, None)
+    }
+
+    #[test]
+    fn wr_delay_counts_days_since_friday() {
+        let cases = [
+            ("2023-03-03T17:00:00+01:00", 0), // Friday
+            ("2023-03-04T10:00:00+01:00", 1), // Saturday
+            ("2023-03-06T09:00:00+01:00", 3), // Monday
+            ("2023-03-09T18:00:00+01:00", 6), // Thursday
+        ];
+        for (date, delay) in cases {
+            assert_eq!(wr_sent_at(date).wr_delay(), delay, "{}", date);
+        }
+    }
 }
@@ -0,0 +1,17 @@
+// Helpers shared by the personal and the team WRapped
+
+function winnerEmojis(i) {
+  if (i == 1) return "🏆";
+  if (i == 2) return "🥈";
+  if (i == 3) return "🥉";
+  return ".";
+}
+
+function switchPalette(year) {
+  year = parseInt(year);
+  if (year <= 2023 && year >= 2021) {
+    document.body.className = "palette-" + year;
+  } else {
+    document.body.className = "palette-default";
+  }
+}
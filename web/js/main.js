@@ -1,3 +1,23 @@
+// The version of the stats file this page was written for, see `cargo run -- schema`
+const SCHEMA_VERSION = 2;
+
+function checkSchemaVersion(data) {
+  if ((data.schema_version || 0) > SCHEMA_VERSION) {
+    console.warn(`The stats have schema version ${data.schema_version}, but only version ${SCHEMA_VERSION} is supported`);
+  }
+  return data;
+}
+
+// The stats are embedded into the page by `export-html`, otherwise they are served
+function loadStats(url) {
+  if (window.WRAPPED_STATS) {
+    return Promise.resolve(checkSchemaVersion(window.WRAPPED_STATS));
+  }
+  return fetch(url)
+    .then(response => response.json())
+    .then(checkSchemaVersion);
+}
+
 function get_dimensions(id) {
   var element = document.getElementById(id);
   var x = element.offsetWidth;
@@ -334,17 +354,295 @@ function drawTimeOfDayChart(container_id, timeOfDayData) {
       .attr("width", width + margin.left + margin.right);
 }
 
-function winnerEmojis(i) {
-  if (i == 1) return "🏆";
-  if (i == 2) return "🥈";
-  if (i == 3) return "🥉";
-  return ".";
+function drawHeatmap(container_id, heatmapData) {
+  // Clear existing content
+  d3.select("#" + container_id).select('svg').remove();
+
+  const margin = { top: 20, right: 30, bottom: 40, left: 60 };
+
+  // Get new dimensions of the container
+  const container_dimensions = get_dimensions(container_id);
+  const width = container_dimensions.width - margin.left - margin.right;
+  const height = container_dimensions.height - margin.top - margin.bottom;
+
+  // Flatten the 7x24 matrix (Monday first) into an array of cells
+  const data = heatmapData.flatMap((hours, day) => {
+    return hours.map((count, hour) => ({ day, hour, count }));
+  });
+
+  // Create SVG element
+  const svg = d3.select("#" + container_id)
+    .append("svg")
+      .attr("width", width + margin.left + margin.right)
+      .attr("height", height + margin.top + margin.bottom)
+    .append("g")
+      .attr("transform", `translate(${margin.left}, ${margin.top})`);
+
+  // Define the scales
+  const x = d3.scaleBand()
+    .range([0, width])
+    .domain(d3.range(0, 24))
+    .padding(0.1);
+
+  const y = d3.scaleBand()
+    .range([0, height])
+    .domain(d3.range(0, 7))
+    .padding(0.1);
+
+  const opacity = d3.scaleLinear()
+    .range([0.1, 1])
+    .domain([0, d3.max(data, d => d.count) || 1]);
+
+  const xAxis = d3.axisBottom(x)
+    .tickValues(d3.range(0, 24, 3))
+    .tickFormat(d => `${d}:00`)
+    .tickSize(0)
+    .tickPadding(10);
+
+  const yAxis = d3.axisLeft(y)
+    .tickFormat(d => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"][d])
+    .tickSize(0)
+    .tickPadding(10);
+
+  // Add the X-axis
+  svg.append("g")
+    .attr("transform", `translate(0, ${height})`)
+    .call(xAxis)
+    .selectAll("text")
+    .attr("fill", "var(--text-grey)")
+    .attr("font-size", "14px");
+
+  // Add the Y-axis
+  svg.append("g")
+    .call(yAxis)
+    .selectAll("text")
+    .attr("fill", "var(--text-grey)")
+    .attr("font-size", "14px")
+    .attr("font-weight", "bold");
+
+  // Remove the axis lines and ticks
+  svg.selectAll(".domain, tick line").remove();
+
+  // Create the cells
+  svg.selectAll(".cell")
+    .data(data)
+    .join("rect")
+      .attr("class", "cell")
+      .attr("x", d => x(d.hour))
+      .attr("y", d => y(d.day))
+      .attr("width", x.bandwidth())
+      .attr("height", y.bandwidth())
+      .attr("rx", 4)
+      .attr("ry", 4)
+      .attr("fill", "var(--color-4)")
+      .attr("fill-opacity", 0)
+    .transition()
+      .duration(1500)
+      .delay(d => d.hour * 30)
+      .attr("fill-opacity", d => d.count > 0 ? opacity(d.count) : 0.05);
 }
 
-function updateCCList(ccData) {
-  // Transform the data into an array and sort it
+function drawWordsChart(container_id, wordCounts) {
+  // Clear existing content
+  d3.select("#" + container_id).select('svg').remove();
+
+  const margin = { top: 20, right: 30, bottom: 40, left: 60 };
+
+  // Get new dimensions of the container
+  const container_dimensions = get_dimensions(container_id);
+  const width = container_dimensions.width - margin.left - margin.right;
+  const height = container_dimensions.height - margin.top - margin.bottom;
+
+  // Transform into an array of objects sorted by date
+  const parseDate = d3.timeParse("%Y-%m-%d");
+  const data = wordCounts.map(d => {
+    return { date: parseDate(d.date), count: d.num_words };
+  }).sort((a, b) => a.date - b.date);
+
+  // Create SVG element
+  const svg = d3.select("#" + container_id)
+    .append("svg")
+      .attr("width", width + margin.left + margin.right)
+      .attr("height", height + margin.top + margin.bottom)
+    .append("g")
+      .attr("transform", `translate(${margin.left}, ${margin.top})`);
+
+  // Define the scales
+  const x = d3.scaleTime()
+    .domain(d3.extent(data, d => d.date))
+    .range([0, width]);
+
+  const y = d3.scaleLinear()
+    .domain([0, d3.max(data, d => d.count) || 1])
+    .range([height, 0]);
+
+  const xAxis = d3.axisBottom(x)
+    .ticks(d3.timeMonth.every(1))
+    .tickFormat(d3.timeFormat("%b"))
+    .tickSize(0)
+    .tickPadding(15);
+
+  const yAxis = d3.axisLeft(y)
+    .ticks(5)
+    .tickSize(0)
+    .tickPadding(20);
+
+  // Add the X-axis
+  svg.append("g")
+    .attr("transform", `translate(0, ${height})`)
+    .call(xAxis)
+    .selectAll("text")
+    .attr("fill", "var(--text-grey)")
+    .attr("font-size", "14px");
+
+  // Add the Y-axis
+  svg.append("g")
+    .call(yAxis)
+    .selectAll("text")
+    .attr("fill", "var(--text-grey)")
+    .attr("font-size", "16px")
+    .attr("font-weight", "bold");
+
+  // Remove the axis lines and ticks
+  svg.selectAll(".domain, tick line").remove();
+
+  const defs = svg.append("defs");
+
+  // Define a clip path
+  defs.append("clipPath")
+    .attr("id", "words-clip")
+    .append("rect")
+      .attr("width", 0)
+      .attr("height", height + margin.top + margin.bottom);
+
+  const line = d3.line()
+    .x(d => x(d.date))
+    .y(d => y(d.count))
+    .curve(d3.curveMonotoneX);
+
+  svg.append("path")
+    .datum(data)
+    .attr("fill", "none")
+    .attr("clip-path", "url(#words-clip)")
+    .attr("stroke", "var(--color-4)")
+    .attr("stroke-width", 2.5)
+    .attr("d", line);
+
+  svg.selectAll(".word-dot")
+    .data(data)
+    .join("circle")
+      .attr("class", "word-dot")
+      .attr("clip-path", "url(#words-clip)")
+      .attr("cx", d => x(d.date))
+      .attr("cy", d => y(d.count))
+      .attr("r", 3)
+      .attr("fill", "var(--color-3)");
+
+  // Animate the clip path
+  svg.select("#words-clip rect")
+    .transition()
+    .duration(2000)
+    .attr("width", width + margin.left + margin.right);
+}
+
+function drawProjectChart(container_id, projectData) {
+  // Clear existing content
+  d3.select("#" + container_id).select('svg').remove();
+
+  const margin = { top: 20, right: 60, bottom: 20, left: 140 };
+
+  // Get new dimensions of the container
+  const container_dimensions = get_dimensions(container_id);
+  const width = container_dimensions.width - margin.left - margin.right;
+  const height = container_dimensions.height - margin.top - margin.bottom;
+
+  // Transform the data into an array sorted by the number of mentions
+  const data = Object.entries(projectData)
+    .map(([project, count]) => ({ project, count }))
+    .sort((a, b) => b.count - a.count);
+
+  // Create SVG element
+  const svg = d3.select("#" + container_id)
+    .append("svg")
+      .attr("width", width + margin.left + margin.right)
+      .attr("height", height + margin.top + margin.bottom)
+    .append("g")
+      .attr("transform", `translate(${margin.left}, ${margin.top})`);
+
+  // Define the scales
+  const x = d3.scaleLinear()
+    .range([0, width])
+    .domain([0, d3.max(data, d => d.count) || 1]);
+
+  const y = d3.scaleBand()
+    .range([0, height])
+    .domain(data.map(d => d.project))
+    .padding(0.3);
+
+  const yAxis = d3.axisLeft(y)
+    .tickSize(0)
+    .tickPadding(10);
+
+  // Add the Y-axis
+  svg.append("g")
+    .call(yAxis)
+    .selectAll("text")
+    .attr("fill", "var(--text-grey)")
+    .attr("font-size", "16px")
+    .attr("font-weight", "bold");
+
+  // Remove the axis lines and ticks
+  svg.selectAll(".domain, tick line").remove();
+
+  const defs = svg.append("defs");
+
+  const gradient = defs.append("linearGradient")
+    .attr("id", "project-gradient")
+    .attr("x1", "0%").attr("y1", "0%")
+    .attr("x2", "100%").attr("y2", "0%");
+
+  gradient.append("stop")
+    .attr("offset", "0%")
+    .attr("stop-color", "var(--color-3)");
+
+  gradient.append("stop")
+    .attr("offset", "100%")
+    .attr("stop-color", "var(--color-4)");
+
+  // Create the bars
+  svg.selectAll(".project-bar")
+    .data(data)
+    .join("rect")
+      .attr("class", "project-bar")
+      .attr("x", 0)
+      .attr("y", d => y(d.project))
+      .attr("width", 0)
+      .attr("height", y.bandwidth())
+      .attr("fill", "url(#project-gradient)")
+      .attr("rx", y.bandwidth() / 2)
+      .attr("ry", y.bandwidth() / 2)
+    .transition()
+      .duration(1500)
+      .attr("width", d => x(d.count));
+
+  // Add the number of mentions next to the bars
+  svg.selectAll(".project-count")
+    .data(data)
+    .join("text")
+      .attr("class", "project-count")
+      .attr("x", d => x(d.count) + 10)
+      .attr("y", d => y(d.project) + y.bandwidth() / 2)
+      .attr("dominant-baseline", "middle")
+      .attr("fill", "var(--text-grey)")
+      .attr("font-size", "14px")
+      .attr("font-weight", "bold")
+      .text(d => `${d.count}x`);
+}
+
+function updateCCList(ccData, ccNames) {
+  // Transform the data into an array and sort it, showing the display name if known
   const sortedData = Object.entries(ccData)
-    .map(([username, count]) => ({ username, count }))
+    .map(([email, count]) => ({ username: ccNames[email] || '@' + email.split('@')[0], count }))
     .sort((a, b) => b.count - a.count)
     .slice(0, 10); // Only show the top 10
 
@@ -361,7 +659,7 @@ function updateCCList(ccData) {
 
     const usernameSpan = document.createElement('div');
     usernameSpan.className = 'cc-list-username';
-    usernameSpan.textContent = `@${item.username}`;
+    usernameSpan.textContent = item.username;
 
     const countSpan = document.createElement('div');
     countSpan.className = 'cc-list-count';
@@ -375,13 +673,118 @@ function updateCCList(ccData) {
   });
 }
 
-function switchPalette(year) {
-  year = parseInt(year);
-  if (year <= 2023 && year >= 2021) {
-    document.body.className = "palette-" + year;
-  } else {
-    document.body.className = "palette-default";
+function updateWordCloud(topWords) {
+  const cloudContainer = document.getElementById('word-cloud');
+  if (topWords.length === 0) return;
+
+  // Scale the font size between the least and most frequent word
+  const fontSize = d3.scaleSqrt()
+    .domain(d3.extent(topWords, d => d.count))
+    .range([1.0, 3.5]);
+
+  // Shuffle the words such that the largest ones are not all in the beginning
+  d3.shuffle(topWords.slice()).forEach((item, index) => {
+    const wordSpan = document.createElement('span');
+    wordSpan.className = 'word-cloud-word';
+    wordSpan.style.fontSize = `${fontSize(item.count).toFixed(2)}vw`;
+    wordSpan.style.animationDelay = `${index * 0.05}s`;
+    wordSpan.textContent = item.term;
+    wordSpan.title = `${item.count}x`;
+    cloudContainer.appendChild(wordSpan);
+  });
+}
+
+function updateTopicList(topTopics) {
+  const listContainer = document.getElementById('topic-list');
+  topTopics.slice(0, 5).forEach((item, index) => {
+    const listItem = document.createElement('li');
+    listItem.style.animationDelay = `${index * 0.15}s`;
+    listItem.textContent = `${index + 1}. ${item.term}`;
+    listContainer.appendChild(listItem);
+  });
+}
+
+function readingEaseText(score) {
+  if (score >= 80) return "Easy as a children's book";
+  if (score >= 60) return "Plain English";
+  if (score >= 30) return "Fairly academic";
+  return "Only your boss understands it";
+}
+
+function updateWritingStyle(data) {
+  document.getElementById('reading-ease').textContent = data.reading_ease.mean.toFixed();
+  document.getElementById('reading-ease-text').textContent = readingEaseText(data.reading_ease.mean);
+  document.getElementById('sentence-length').textContent = data.sentence_length.mean.toFixed(1);
+  document.getElementById('bullet-ratio').textContent = (data.bullet_ratio.mean * 100).toFixed() + '%';
+  document.getElementById('num-emojis').textContent = data.num_emojis;
+  document.getElementById('num-exclamation-marks').textContent = data.num_exclamation_marks;
+  document.getElementById('num-links').textContent = data.num_links;
+}
+
+function achievementIcon(id) {
+  const icons = {
+    "early-bird": "🐦",
+    "night-owl": "🦉",
+    "perfect-month": "📅",
+    "novelist": "📚",
+    "ghosted": "👻",
+  };
+  return icons[id] || "🏅";
+}
+
+function updateAchievements(achievements) {
+  const listContainer = document.getElementById('achievement-list');
+  if (achievements.length === 0) {
+    listContainer.textContent = "No badges this year, maybe next year!";
+    return;
   }
+  achievements.forEach((item, index) => {
+    const badge = document.createElement('div');
+    badge.className = 'achievement';
+    badge.style.animationDelay = `${index * 0.15}s`;
+
+    const icon = document.createElement('div');
+    icon.className = 'achievement-icon';
+    icon.textContent = achievementIcon(item.id);
+
+    const name = document.createElement('div');
+    name.className = 'achievement-name';
+    name.textContent = item.name;
+
+    const description = document.createElement('div');
+    description.className = 'achievement-description';
+    description.textContent = item.description;
+
+    badge.appendChild(icon);
+    badge.appendChild(name);
+    badge.appendChild(description);
+    listContainer.appendChild(badge);
+  });
+}
+
+// Link to the other years if the server has the stats of several years
+function updateYearNav(currentYear) {
+  if (window.WRAPPED_STATS) {
+    return;
+  }
+  fetch('/api/years')
+    .then(response => response.json())
+    .then(years => {
+      if (years.length < 2) {
+        return;
+      }
+      const nav = document.getElementById('year-nav');
+      years.forEach(year => {
+        const link = document.createElement('a');
+        link.textContent = year;
+        link.href = `/?year=${year}`;
+        if (year === currentYear) {
+          link.classList.add('current-year');
+        }
+        nav.appendChild(link);
+      });
+    })
+    .catch(error => console.error('Error fetching years:', error));
 }
 
 document.addEventListener('DOMContentLoaded', function() {
@@ -393,10 +796,18 @@ document.addEventListener('DOMContentLoaded', function() {
   const delayOfReplyId = 'delay-of-reply';
   const weekdayId = 'weekday-chart-container';
   const timeofdayId = 'timeofday-chart-container';
+  const replyHeatmapId = 'reply-heatmap-container';
+  const wordsChartId = 'words-chart-container';
+  const projectChartId = 'project-chart-container';
+  const wordsTrendId = 'words-trend';
+  const longestWrId = 'longest-wr';
   let year;
   let ratioRepliedWRs;
   let weekdayData;
   let timeofdayData;
+  let replyHeatmapData;
+  let wordCountsData;
+  let projectData;
   let numWrsWritten;
   let numWords;
   let ccData;
@@ -436,6 +847,34 @@ document.addEventListener('DOMContentLoaded', function() {
     }
   }
 
+  function resizeReplyHeatmap() {
+    if (replyHeatmapData !== undefined) {
+      drawHeatmap(replyHeatmapId, replyHeatmapData);
+    }
+  }
+
+  function resizeWordsChart() {
+    if (wordCountsData !== undefined) {
+      drawWordsChart(wordsChartId, wordCountsData);
+    }
+  }
+
+  function resizeProjectChart() {
+    if (projectData !== undefined) {
+      drawProjectChart(projectChartId, projectData);
+    }
+  }
+
+  const wordsTrendContainer = document.getElementById(wordsTrendId);
+  const longestWrContainer = document.getElementById(longestWrId);
+  function updateWordsTrend(trend, longestWr) {
+    const change = Math.round(trend.relative_change * 100);
+    wordsTrendContainer.textContent = Math.abs(change) + "% " + (change < 0 ? "shorter" : "longer");
+    if (longestWr) {
+      longestWrContainer.textContent = `Longest: ${longestWr.num_words} words on ${longestWr.date}`;
+    }
+  }
+
   const delayOfReplyContainer = document.getElementById(delayOfReplyId);
   function updateDelay(delayDays) {
     const delayDaysText = delayDays.toFixed(1) + " days"
@@ -447,8 +886,10 @@ document.addEventListener('DOMContentLoaded', function() {
     yearContainer.textContent = year;
   }
 
-  fetch('/stats/stats.json')
-    .then(response => response.json())
+  // Show the year of e.g. `/?year=2022` if several years are served
+  const requestedYear = new URLSearchParams(window.location.search).get('year');
+  const statsUrl = requestedYear ? `/api/stats?year=${encodeURIComponent(requestedYear)}` : '/api/stats';
+  loadStats(statsUrl)
     .then(data => {
         // Now we have the JSON data
         year = data.year;
@@ -456,23 +897,38 @@ document.addEventListener('DOMContentLoaded', function() {
         ratioRepliedWRs = data.ratio_replied_wrs;
         numWrsWritten = data.num_wrs;
         numWords = data.num_words;
-        delayDays = data.avg_reply_delay;
+        delayDays = data.reply_delay.mean;
         weekdayData = data.weekday_wr_histogram;
-        timeofdayData = data.hour_reply_histogram;
+        timeofdayData = data.hour_wr_histogram;
+        replyHeatmapData = data.weekday_hour_reply_heatmap;
+        wordCountsData = data.word_counts;
+        projectData = data.project_histogram;
         ccData = data.cc_histogram;
         updateYear(year);
+        updateYearNav(year);
         updateNumWrsWritten(numWrsWritten);
         updateNumWords(numWords);
         updateTextOverlay(ratioRepliedWRs);
         updateDelay(delayDays);
-        updateCCList(ccData);
+        updateCCList(ccData, data.cc_names);
+        updateWordsTrend(data.words_trend, data.longest_wr);
+        updateWordCloud(data.top_words);
+        updateTopicList(data.top_topics);
+        updateWritingStyle(data);
+        updateAchievements(data.achievements);
         resizeProgressCircleChart(progressCircleId, ratioRepliedWRs);
         resizeWeekdayChart(weekdayId, weekdayData);
         resizeTimeOfDayChart(timeofdayId, timeofdayData);
+        resizeReplyHeatmap(replyHeatmapId, replyHeatmapData);
+        resizeWordsChart(wordsChartId, wordCountsData);
+        resizeProjectChart(projectChartId, projectData);
         // Set up the resize event listener now that we have data
         window.addEventListener('resize', resizeProgressCircleChart);
         window.addEventListener('resize', resizeWeekdayChart);
         window.addEventListener('resize', resizeTimeOfDayChart);
+        window.addEventListener('resize', resizeReplyHeatmap);
+        window.addEventListener('resize', resizeWordsChart);
+        window.addEventListener('resize', resizeProjectChart);
     })
     .catch(error => {
         console.error('Error fetching data:', error);
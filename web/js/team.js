@@ -0,0 +1,47 @@
+function updateLeaderboard(container_id, entries, formatValue) {
+  // Create list elements, only showing the top 10
+  const listContainer = document.getElementById(container_id);
+  entries.slice(0, 10).forEach((item, index) => {
+    const listItem = document.createElement('li');
+    listItem.style.animationDelay = `${index * 0.15}s`;
+
+    const placeSpan = document.createElement('div');
+    placeSpan.className = 'cc-list-place';
+    placeSpan.textContent = `${winnerEmojis(index + 1)}`;
+
+    const usernameSpan = document.createElement('div');
+    usernameSpan.className = 'cc-list-username';
+    usernameSpan.textContent = `@${item.name.split('@')[0]}`;
+
+    const countSpan = document.createElement('div');
+    countSpan.className = 'cc-list-count';
+    countSpan.textContent = formatValue(item.value);
+
+    listItem.appendChild(placeSpan);
+    listItem.appendChild(usernameSpan);
+    listItem.appendChild(countSpan);
+
+    listContainer.appendChild(listItem);
+  });
+}
+
+document.addEventListener('DOMContentLoaded', function() {
+  fetch('/api/team')
+    .then(response => response.json())
+    .then(data => {
+        const year = data.years.join(", ");
+        switchPalette(data.years[data.years.length - 1]);
+        document.getElementById('team-wrapped-year').textContent = year;
+        document.getElementById('team-num-wrs').textContent = data.num_wrs + " WRs";
+        document.getElementById('team-num-words').textContent = parseInt(data.num_words / 1000) + "k";
+        document.getElementById('team-num-members').textContent = data.num_members;
+        document.getElementById('team-reply-ratio').textContent = (data.ratio_replied_wrs * 100).toFixed() + '%';
+        updateLeaderboard('most-wrs-list', data.most_wrs, value => `${value}x`);
+        updateLeaderboard('longest-streak-list', data.longest_streak, value => `${value} weeks`);
+        updateLeaderboard('most-punctual-list', data.most_punctual, value => `${value.toFixed(1)} days`);
+    })
+    .catch(error => {
+        console.error('Error fetching data:', error);
+        document.getElementById('team-num-wrs').textContent = 'Sth failed.';
+    });
+});